@@ -0,0 +1,47 @@
+#![allow(dead_code)]
+
+use glam::DVec2;
+use ropes_sim::state::ropes::{Ropes, StickKind};
+
+pub const SPACING: f64 = 20.;
+
+/// A square grid of points joined to their horizontal and vertical neighbours, hanging from the top row.
+pub fn cloth(size: usize) -> Ropes {
+    let mut ropes = Ropes::new();
+    let mut keys = Vec::with_capacity(size * size);
+    for y in 0..size {
+        for x in 0..size {
+            keys.push(ropes.add_point(DVec2::new(x as f64, y as f64) * SPACING));
+        }
+    }
+    for &key in keys.iter().take(size) {
+        ropes.toggle_locked(key);
+    }
+    for y in 0..size {
+        for x in 0..size {
+            let key = keys[y * size + x];
+            if x + 1 < size {
+                ropes.add_stick(key, keys[y * size + x + 1], StickKind::Rod);
+            }
+            if y + 1 < size {
+                ropes.add_stick(key, keys[(y + 1) * size + x], StickKind::Rod);
+            }
+        }
+    }
+    ropes.seed(0);
+    ropes
+}
+
+/// A horizontal chain of points joined by sticks of the kind, fixed at its first point.
+pub fn rope(length: usize, kind: StickKind) -> Ropes {
+    let mut ropes = Ropes::new();
+    let mut last = ropes.add_point(DVec2::ZERO);
+    ropes.toggle_locked(last);
+    for x in 1..length {
+        let key = ropes.add_point(DVec2::new(x as f64 * SPACING, 0.));
+        ropes.add_stick(last, key, kind);
+        last = key;
+    }
+    ropes.seed(0);
+    ropes
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use common::SPACING;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use glam::DVec2;
+use ropes_sim::state::ropes::StickKind;
+
+const RADIUS: f64 = 12.;
+
+fn tick(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tick");
+    for (name, kind) in [("rope", StickKind::Rod), ("slack_rope", StickKind::Rope)] {
+        for length in [64, 256, 1024] {
+            let mut ropes = common::rope(length, kind);
+            group.bench_function(BenchmarkId::new(name, length), |b| b.iter(|| ropes.tick()));
+        }
+    }
+    for size in [16, 32, 64] {
+        let mut ropes = common::cloth(size);
+        group.bench_function(BenchmarkId::new("cloth", size), |b| b.iter(|| ropes.tick()));
+    }
+    group.finish();
+}
+
+fn remove(c: &mut Criterion) {
+    let mut group = c.benchmark_group("remove");
+    for size in [32, 128] {
+        let ropes = common::cloth(size);
+        let y = size as f64 * SPACING / 2.;
+        let (last, current) = (DVec2::new(-SPACING, y), DVec2::new(SPACING, y));
+        group.bench_function(BenchmarkId::new("sticks", size), |b| {
+            b.iter_batched_ref(
+                || ropes.clone(),
+                |ropes| ropes.remove_sticks(last, current),
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new("points", size), |b| {
+            b.iter_batched_ref(
+                || ropes.clone(),
+                |ropes| ropes.remove_points(last, current, RADIUS),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn get_point(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get point");
+    for size in [32, 128] {
+        let ropes = common::cloth(size);
+        let positions: Vec<DVec2> = (0..size)
+            .map(|i| DVec2::splat(i as f64 * SPACING + 5.))
+            .collect();
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            b.iter(|| {
+                positions
+                    .iter()
+                    .filter_map(|&position| ropes.get_point(position, RADIUS))
+                    .count()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, tick, remove, get_point);
+criterion_main!(benches);
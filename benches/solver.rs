@@ -0,0 +1,19 @@
+mod common;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+fn tick(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cloth tick");
+    for size in [32, 64, 128] {
+        for parallel in [false, true] {
+            let mut ropes = common::cloth(size);
+            ropes.set_parallel(parallel);
+            let name = if parallel { "parallel" } else { "sequential" };
+            group.bench_function(BenchmarkId::new(name, size), |b| b.iter(|| ropes.tick()));
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, tick);
+criterion_main!(benches);
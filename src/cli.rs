@@ -0,0 +1,191 @@
+use crate::state::ropes::{Ropes, Statistics};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use glam::DVec2;
+use serde::Serialize;
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+};
+
+pub const DEFAULT_SCENE: &str = "scene.json";
+
+/// A rope physics simulation
+#[derive(Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Solve sticks on multiple threads, which is faster for large scenes
+    #[arg(long, global = true)]
+    pub parallel: bool,
+}
+
+impl Cli {
+    pub fn command(&mut self) -> Command {
+        self.command.take().unwrap_or(Command::Edit {
+            scene: None,
+            theme: None,
+            texture: None,
+        })
+    }
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Open the editor, loading the scene if it exists and saving to it with S
+    Edit {
+        scene: Option<PathBuf>,
+        /// Colour theme: dark, light, high-contrast or a theme file
+        #[arg(short, long)]
+        theme: Option<String>,
+        /// Image drawn across filled cloth
+        #[arg(long)]
+        texture: Option<PathBuf>,
+    },
+    /// Simulate a scene without opening a window and dump the final state
+    Run(Run),
+}
+
+#[derive(Args)]
+pub struct Run {
+    /// Scene file saved from the editor
+    scene: PathBuf,
+    /// Number of ticks to simulate
+    #[arg(short, long, default_value_t = 320)]
+    ticks: u32,
+    /// Seed for the order sticks are solved in, for reproducible runs
+    #[arg(short, long)]
+    seed: Option<u64>,
+    /// Output format; csv only contains the final point positions
+    #[arg(short, long, value_enum, default_value_t = Format::Json)]
+    format: Format,
+    /// File to write to instead of stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Json,
+    Csv,
+}
+
+#[derive(Serialize)]
+struct Report {
+    ticks: u32,
+    statistics: Statistics,
+    points: Vec<PointReport>,
+}
+
+#[derive(Serialize)]
+struct PointReport {
+    key: usize,
+    position: DVec2,
+    locked: bool,
+}
+
+pub fn run(args: Run, parallel: bool) -> io::Result<()> {
+    let mut ropes = Ropes::load(&args.scene)?;
+    ropes.set_parallel(parallel);
+    if let Some(seed) = args.seed {
+        ropes.seed(seed);
+    }
+    for _ in 0..args.ticks {
+        ropes.tick();
+    }
+
+    let mut output: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+
+    match args.format {
+        Format::Json => {
+            let report = Report {
+                ticks: args.ticks,
+                statistics: ropes.statistics(),
+                points: ropes
+                    .get_points(1.)
+                    .map(|(key, position, locked)| PointReport {
+                        key,
+                        position,
+                        locked,
+                    })
+                    .collect(),
+            };
+            serde_json::to_writer_pretty(&mut output, &report)?;
+            writeln!(output)?;
+        }
+        Format::Csv => {
+            writeln!(output, "key,x,y,locked")?;
+            for (key, position, locked) in ropes.get_points(1.) {
+                writeln!(output, "{},{},{},{}", key, position.x, position.y, locked)?;
+            }
+        }
+    }
+
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::ropes::StickKind;
+    use clap::Parser;
+    use std::{fs, process};
+
+    // runs a short rope of three points, returning what was written
+    fn run_scene(format: &str) -> String {
+        let directory = std::env::temp_dir();
+        let scene = directory.join(format!("ropes_run_{}_{}.json", format, process::id()));
+        let output = directory.join(format!("ropes_run_{}_{}.out", format, process::id()));
+        let mut ropes = Ropes::new();
+        let keys = [0., 50., 100.].map(|x| ropes.add_point(DVec2::new(x, 0.)));
+        ropes.toggle_locked(keys[0]);
+        for pair in keys.windows(2) {
+            ropes.add_stick(pair[0], pair[1], StickKind::Rod);
+        }
+        ropes.save(&scene).unwrap();
+
+        let mut cli = Cli::parse_from([
+            "ropes_sim",
+            "run",
+            scene.to_str().unwrap(),
+            "--ticks",
+            "10",
+            "--seed",
+            "0",
+            "--format",
+            format,
+            "--output",
+            output.to_str().unwrap(),
+        ]);
+        let result = match cli.command() {
+            Command::Run(args) => run(args, cli.parallel),
+            Command::Edit { .. } => panic!("parsed as edit"),
+        };
+        let written = fs::read_to_string(&output);
+        fs::remove_file(&scene).unwrap();
+        fs::remove_file(&output).ok();
+        result.unwrap();
+        written.unwrap()
+    }
+
+    #[test]
+    fn run_writes_a_csv_row_per_point() {
+        let written = run_scene("csv");
+        let mut lines = written.lines();
+        assert_eq!(lines.next(), Some("key,x,y,locked"));
+        assert_eq!(lines.count(), 3);
+    }
+
+    #[test]
+    fn run_writes_json_statistics_and_points() {
+        let written = run_scene("json");
+        let report: serde_json::Value = serde_json::from_str(&written).unwrap();
+        assert_eq!(report["ticks"], 10);
+        assert!(report["statistics"].is_object());
+        assert_eq!(report["points"].as_array().unwrap().len(), 3);
+    }
+}
@@ -1,5 +1,6 @@
 use enum_map::{Enum, EnumMap};
 use ggez::{
+    graphics,
     input::{
         keyboard::{self, KeyCode as K},
         mouse::{self, MouseButton as M},
@@ -16,6 +17,34 @@ pub enum Input {
     Alternate,
     ToggleSimulating,
     Clear,
+    Save,
+    Bend,
+    SwitchKind,
+    Pulley,
+    Slider,
+    Body,
+    Wind,
+    Drag,
+    Force,
+    Water,
+    Motorise,
+    ReelIn,
+    ReelOut,
+    Weld,
+    Pin,
+    Colour,
+    SwitchTheme,
+    Fill,
+    ToggleSmooth,
+    ZoomIn,
+    ZoomOut,
+    Fit,
+    ToggleFollow,
+    ToggleStress,
+    ToggleHud,
+    TogglePlot,
+    ToggleVelocities,
+    Trace,
     Quit,
 }
 
@@ -24,6 +53,7 @@ pub struct Inputs {
     last: EnumMap<Input, bool>,
     mouse_position: IVec2,
     last_mouse: IVec2,
+    window_size: IVec2,
 }
 
 impl Inputs {
@@ -33,6 +63,7 @@ impl Inputs {
             last: EnumMap::default(),
             mouse_position: IVec2::ZERO,
             last_mouse: IVec2::ZERO,
+            window_size: IVec2::ZERO,
         }
     }
 
@@ -48,6 +79,34 @@ impl Inputs {
                 K::Space => inputs[ToggleSimulating] = true,
                 K::Escape => inputs[Quit] = true,
                 K::R => inputs[Clear] = true,
+                K::S => inputs[Save] = true,
+                K::B => inputs[Bend] = true,
+                K::Tab => inputs[SwitchKind] = true,
+                K::P => inputs[Pulley] = true,
+                K::L => inputs[Slider] = true,
+                K::G => inputs[Body] = true,
+                K::W => inputs[Wind] = true,
+                K::D => inputs[Drag] = true,
+                K::F => inputs[Force] = true,
+                K::V => inputs[Water] = true,
+                K::M => inputs[Motorise] = true,
+                K::Up => inputs[ReelIn] = true,
+                K::Down => inputs[ReelOut] = true,
+                K::J => inputs[Weld] = true,
+                K::N => inputs[Pin] = true,
+                K::C => inputs[Colour] = true,
+                K::K => inputs[SwitchTheme] = true,
+                K::U => inputs[Fill] = true,
+                K::O => inputs[ToggleSmooth] = true,
+                K::Equals => inputs[ZoomIn] = true,
+                K::Minus => inputs[ZoomOut] = true,
+                K::Z => inputs[Fit] = true,
+                K::Q => inputs[ToggleFollow] = true,
+                K::T => inputs[ToggleStress] = true,
+                K::H => inputs[ToggleHud] = true,
+                K::E => inputs[TogglePlot] = true,
+                K::A => inputs[ToggleVelocities] = true,
+                K::X => inputs[Trace] = true,
                 K::LShift => inputs[Alternate] = true,
                 _ => (),
             }
@@ -64,6 +123,8 @@ impl Inputs {
         let mouse_position = mouse::position(ctx);
         self.mouse_position.x = mouse_position.x as i32;
         self.mouse_position.y = mouse_position.y as i32;
+        let (width, height) = graphics::drawable_size(ctx);
+        self.window_size = IVec2::new(width as i32, height as i32);
     }
 
     pub fn last(&self, input: Input) -> bool {
@@ -77,6 +138,16 @@ impl Inputs {
     pub fn last_mouse(&self) -> IVec2 {
         self.last_mouse
     }
+
+    pub fn window_size(&self) -> IVec2 {
+        self.window_size
+    }
+}
+
+impl Default for Inputs {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Index<Input> for Inputs {
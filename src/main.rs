@@ -1,8 +1,4 @@
-#![windows_subsystem = "windows"]
-
-mod input;
-mod state;
-
+use clap::Parser;
 use ggez::{
     conf::{NumSamples, WindowMode, WindowSetup},
     event::{
@@ -10,12 +6,34 @@ use ggez::{
         winit_event::{Event, WindowEvent},
         ControlFlow,
     },
+    graphics::Image,
     timer, ContextBuilder, GameResult,
 };
-use input::{Input, Inputs};
-use state::State;
+use ropes_sim::{
+    cli::{self, Cli, Command},
+    input::{Input, Inputs},
+    state::{theme::Theme, State},
+};
+use std::fs;
 
 fn main() -> GameResult {
+    let mut cli = Cli::parse();
+    let (scene, theme, texture) = match cli.command() {
+        Command::Run(args) => return Ok(cli::run(args, cli.parallel)?),
+        Command::Edit {
+            scene,
+            theme,
+            texture,
+        } => (
+            scene.unwrap_or_else(|| cli::DEFAULT_SCENE.into()),
+            match theme {
+                Some(name) => Theme::find(&name)?,
+                None => Theme::default(),
+            },
+            texture,
+        ),
+    };
+
     let window_mode = WindowMode::default().dimensions(1700., 900.);
     let window_setup = WindowSetup::default()
         .title("ropes")
@@ -27,7 +45,11 @@ fn main() -> GameResult {
         .window_setup(window_setup)
         .build()?;
 
-    let mut state = State::new();
+    let texture = match texture {
+        Some(path) => Some(Image::from_bytes(&mut ctx, &fs::read(path)?)?),
+        None => None,
+    };
+    let mut state = State::new(scene, theme, texture, cli.parallel)?;
     let mut inputs = Inputs::new();
     inputs.update(&mut ctx);
 
@@ -1,128 +1,378 @@
 mod rendering;
-mod ropes;
+pub mod ropes;
+pub mod theme;
 
 use crate::input::{self, Inputs};
-use glam::IVec2;
-use ropes::Ropes;
+use ggez::graphics::Image;
+use glam::{DVec2, IVec2};
+use rendering::Minimap;
+use ropes::{Field, Motor, Ropes, StickKind};
+use std::{
+    collections::{HashMap, VecDeque},
+    io, mem,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+use theme::Theme;
 
 const TPS: u64 = 32;
 const RADIUS: f64 = 12.;
+const BENDING: [f64; 3] = [0.02, 0.1, 0.5];
+// wind speeds to the right, with their turbulence
+const WINDS: [(f64, f64); 2] = [(150., 0.5), (400., 1.)];
+const DRAG: [f64; 3] = [0.05, 0.2, 0.5];
+const FIELD_RADIUS: f64 = 200.;
+const FIELD_STRENGTH: f64 = 3000.;
+const WINCH_SPEED: f64 = 60.;
+// colours and widths sticks can be given over the theme's, in the order they are cycled through
+const PALETTE: [(u8, u8, u8); 6] = [
+    (230, 80, 80),
+    (240, 170, 60),
+    (240, 230, 90),
+    (90, 210, 110),
+    (80, 170, 240),
+    (180, 110, 240),
+];
+const WIDTHS: [f32; 4] = [1., 3., 8., 12.];
+// how far oscillating sticks stretch and shrink, as a fraction of their length, and how often
+const OSCILLATION: f64 = 0.3;
+const OSCILLATION_PERIOD: f64 = 2.;
+// how many ticks back the trails of traced points go
+const TRAIL_LENGTH: usize = 64;
+// how many ticks back the plots go
+const PLOT_LENGTH: usize = 256;
+// how much of the debug timings comes from the latest frame, smoothing them enough to read
+const SMOOTHING: f64 = 0.05;
+// how much each press zooms in or out, and how far either way it can go
+const ZOOM_STEP: f64 = 1.25;
+const MIN_ZOOM: f64 = 0.05;
+const MAX_ZOOM: f64 = 8.;
+// the space left around the scene when fitting it to the window
+const FIT_MARGIN: f64 = 60.;
 
 const TICK_DURATION: f64 = 1. / TPS as f64;
 
 pub struct State {
+    path: PathBuf,
+    parallel: bool,
     accumulator: f64,
     saved: Ropes,
     active: Ropes,
-    camera: IVec2,
+    // the world position at the top left of the window
+    camera: DVec2,
+    // how many pixels across each unit of the world is drawn
+    zoom: f64,
+    follow: Follow,
+    kind: StickKind,
+    theme: Theme,
+    // drawn across filled faces instead of the theme's cloth colour
+    texture: Option<Image>,
+    // draws chains of sticks as curves rather than straight segments
+    smooth: bool,
+    stress: bool,
+    hud: bool,
+    velocities: bool,
+    // the recent positions of traced points, oldest first
+    trails: HashMap<usize, VecDeque<DVec2>>,
+    plot: bool,
+    // kinetic energy, potential energy and rms stick error after each recent tick, oldest first
+    history: VecDeque<[f64; 3]>,
+    // smoothed over recent frames
+    ticks_per_frame: f64,
+    seconds_per_tick: f64,
     simulating: bool,
     action: Action,
 }
 
 impl State {
-    pub fn new() -> Self {
-        Self {
+    pub fn new(
+        path: PathBuf,
+        theme: Theme,
+        texture: Option<Image>,
+        parallel: bool,
+    ) -> io::Result<Self> {
+        let saved = if path.exists() {
+            Ropes::load(&path)?
+        } else {
+            Ropes::new()
+        };
+
+        Ok(Self {
+            path,
+            parallel,
             accumulator: 0.,
-            saved: Ropes::new(),
+            saved,
             active: Ropes::new(),
-            camera: IVec2::ZERO,
+            camera: DVec2::ZERO,
+            zoom: 1.,
+            follow: Follow::None,
+            kind: StickKind::Rod,
+            theme,
+            texture,
+            smooth: false,
+            stress: false,
+            hud: false,
+            velocities: false,
+            trails: HashMap::new(),
+            plot: false,
+            history: VecDeque::new(),
+            ticks_per_frame: 0.,
+            seconds_per_tick: 0.,
             simulating: false,
             action: Action::None,
-        }
+        })
     }
 
     pub fn update(&mut self, dt: f64, inputs: &Inputs) {
         use input::Input::*;
 
+        let window = inputs.window_size();
+        let minimap = Minimap::new(
+            if self.simulating {
+                &self.active
+            } else {
+                &self.saved
+            },
+            self.camera,
+            window.as_dvec2() / self.zoom,
+            window.as_dvec2(),
+        );
+        let mouse = inputs.mouse_position();
+        let jumping = match self.action {
+            Action::Jumping => inputs[LeftMouse],
+            Action::None => inputs[LeftMouse] && !inputs.last(LeftMouse) && minimap.contains(mouse),
+            _ => false,
+        };
+        if jumping {
+            self.action = Action::Jumping;
+            self.camera = minimap.to_world(mouse) - window.as_dvec2() / self.zoom / 2.;
+        } else if let Action::Jumping = self.action {
+            self.action = Action::None;
+        }
+
         if self.simulating {
             match self.action {
                 Action::Panning => {
                     if inputs[Alternate] {
                         if inputs[RightMouse] {
-                            self.camera += inputs.last_mouse() - inputs.mouse_position();
+                            self.camera += (inputs.last_mouse() - inputs.mouse_position())
+                                .as_dvec2()
+                                / self.zoom;
                         }
                     } else {
                         self.action = Action::None;
                     }
                 }
-                Action::None => {
-                    if inputs[RightMouse] && inputs[Alternate] {
-                        self.action = Action::Panning;
-                    }
+                Action::None if inputs[RightMouse] && inputs[Alternate] => {
+                    self.action = Action::Panning;
                 }
-                Action::Deleting(_) => {
-                    if !inputs[RightMouse] {
-                        self.action = Action::None;
-                    }
+                Action::Deleting(_) if !inputs[RightMouse] => {
+                    self.action = Action::None;
                 }
                 _ => (),
             }
 
-            let mouse = inputs.mouse_position() + self.camera;
+            let mouse = self.to_world(inputs.mouse_position());
+
+            self.active
+                .set_winding(match (inputs[ReelIn], inputs[ReelOut]) {
+                    (true, false) => -1.,
+                    (false, true) => 1.,
+                    _ => 0.,
+                });
 
             self.accumulator += dt;
+            let (mut ticks, mut solving) = (0, Duration::ZERO);
             while self.accumulator >= TICK_DURATION {
                 if let Action::Deleting(last) = self.action {
-                    self.active.remove_sticks(last.as_dvec2(), mouse.as_dvec2());
-                    self.active
-                        .remove_points(last.as_dvec2(), mouse.as_dvec2(), RADIUS);
+                    self.active.remove_sticks(last, mouse);
+                    self.active.remove_points(last, mouse, RADIUS);
                 }
-                if let Action::Panning = self.action {
+                if let Action::Panning | Action::Jumping = self.action {
                 } else {
                     if inputs[RightMouse] {
-                        self.action = Action::Deleting(inputs.mouse_position() + self.camera);
+                        self.action = Action::Deleting(self.to_world(inputs.mouse_position()));
                     } else {
                         self.action = Action::None;
                     }
                 }
 
+                let start = Instant::now();
                 self.active.tick();
+                solving += start.elapsed();
                 self.accumulator -= TICK_DURATION;
+                ticks += 1;
+
+                if self.plot {
+                    let statistics = self.active.statistics();
+                    self.history.push_back([
+                        statistics.kinetic_energy,
+                        statistics.potential_energy,
+                        statistics.rms_error,
+                    ]);
+                    if self.history.len() > PLOT_LENGTH {
+                        self.history.pop_front();
+                    }
+                }
+
+                for (key, position, _) in self.active.get_points(1.) {
+                    if let Some(trail) = self.trails.get_mut(&key) {
+                        trail.push_back(position);
+                        if trail.len() > TRAIL_LENGTH {
+                            trail.pop_front();
+                        }
+                    }
+                }
+            }
+
+            self.ticks_per_frame += (ticks as f64 - self.ticks_per_frame) * SMOOTHING;
+            if ticks > 0 {
+                let seconds = solving.as_secs_f64() / ticks as f64;
+                self.seconds_per_tick += (seconds - self.seconds_per_tick) * SMOOTHING;
             }
         } else {
             if inputs[Clear] {
                 self.saved = Ropes::new();
                 self.action.reset();
-                self.camera = IVec2::ZERO;
+                self.camera = DVec2::ZERO;
+                self.zoom = 1.;
+                self.follow = Follow::None;
+                self.trails.clear();
+            }
+
+            if inputs[Save] && !inputs.last(Save) {
+                if let Err(error) = self.saved.save(&self.path) {
+                    eprintln!("could not save {}: {}", self.path.display(), error);
+                }
             }
 
             if let Action::Panning = self.action {
                 if inputs[Alternate] {
                     if inputs[RightMouse] {
-                        self.camera += inputs.last_mouse() - inputs.mouse_position();
+                        self.camera +=
+                            (inputs.last_mouse() - inputs.mouse_position()).as_dvec2() / self.zoom;
                     }
                 } else {
                     self.action = Action::None;
                 }
             }
 
-            let mouse = inputs.mouse_position() + self.camera;
+            if inputs[SwitchKind] && !inputs.last(SwitchKind) {
+                self.kind = match self.kind {
+                    StickKind::Rod => StickKind::Rope,
+                    StickKind::Rope => StickKind::Rod,
+                };
+            }
 
-            match &mut self.action {
-                Action::CreatingPoint => {
-                    if !inputs[LeftMouse] {
-                        if self
-                            .saved
-                            .get_point(mouse.as_dvec2(), RADIUS * 2.)
-                            .is_none()
-                        {
-                            self.saved.add_point(mouse.as_dvec2());
+            let mouse = self.to_world(inputs.mouse_position());
+
+            if inputs[Bend] && !inputs.last(Bend) {
+                if let Some(key) = self.saved.get_point(mouse, RADIUS) {
+                    let stiffness = match self.saved.bending(key) {
+                        Some(stiffness) => BENDING.iter().skip_while(|&&s| s != stiffness).nth(1),
+                        None => BENDING.first(),
+                    };
+                    self.saved.set_bending(key, stiffness.copied());
+                }
+            }
+
+            if inputs[Body] && !inputs.last(Body) {
+                if let Some(key) = self.saved.get_point(mouse, RADIUS) {
+                    self.saved.toggle_body(key);
+                }
+            }
+
+            if inputs[Fill] && !inputs.last(Fill) {
+                if let Some(key) = self.saved.get_point(mouse, RADIUS) {
+                    self.saved.toggle_faces(key);
+                }
+            }
+
+            if inputs[Motorise] && !inputs.last(Motorise) {
+                if let Some(key) = self.saved.get_stick(mouse, RADIUS / 2.) {
+                    let motor = match self.saved.motor(key) {
+                        None => Some(Motor::Winch { speed: WINCH_SPEED }),
+                        Some(Motor::Winch { .. }) => {
+                            self.saved.length(key).map(|length| Motor::Oscillate {
+                                length,
+                                amplitude: length * OSCILLATION,
+                                period: OSCILLATION_PERIOD,
+                            })
                         }
-                        self.action = Action::None;
+                        Some(Motor::Oscillate { .. }) => None,
+                    };
+                    self.saved.set_motor(key, motor);
+                }
+            }
+
+            if inputs[Colour] && !inputs.last(Colour) {
+                if let Some(key) = self.saved.get_stick(mouse, RADIUS / 2.) {
+                    let mut style = self.saved.style(key).unwrap_or_default();
+                    if inputs[Alternate] {
+                        style.width = cycle(&WIDTHS, style.width);
+                    } else {
+                        style.colour = cycle(&PALETTE, style.colour);
+                    }
+                    self.saved.set_style(key, style);
+                }
+            }
+
+            if inputs[Wind] && !inputs.last(Wind) {
+                self.cycle_wind();
+            }
+
+            if inputs[Drag] && !inputs.last(Drag) {
+                let drag = if self.saved.drag() == 0. {
+                    DRAG.first()
+                } else {
+                    DRAG.iter().skip_while(|&&d| d != self.saved.drag()).nth(1)
+                };
+                self.saved.set_drag(drag.copied().unwrap_or(0.));
+            }
+
+            if inputs[Force] && !inputs.last(Force) {
+                self.cycle_field(mouse);
+            }
+
+            if inputs[Water] && !inputs.last(Water) {
+                self.pour(mouse);
+            }
+
+            for (input, joint) in [
+                (Pulley, Joint::Pulley),
+                (Slider, Joint::Slider),
+                (Weld, Joint::Weld),
+                (Pin, Joint::Pin),
+            ] {
+                if inputs[input] && !inputs.last(input) {
+                    self.place(joint, mouse);
+                }
+            }
+
+            match &mut self.action {
+                Action::Placing(..) | Action::Pouring(_)
+                    if inputs[LeftMouse] || inputs[RightMouse] =>
+                {
+                    self.action = Action::None;
+                }
+                Action::CreatingPoint if !inputs[LeftMouse] => {
+                    if self.saved.get_point(mouse, RADIUS * 2.).is_none() {
+                        self.saved.add_point(mouse);
                     }
+                    self.action = Action::None;
                 }
                 Action::CreatingStick(key, end) => {
-                    if let Some(key2) = self.saved.get_point(mouse.as_dvec2(), RADIUS) {
+                    if let Some(key2) = self.saved.get_point(mouse, RADIUS) {
                         *end = StickEnd::Key(key2);
                     } else {
-                        *end = StickEnd::Mouse(inputs.mouse_position());
+                        *end = StickEnd::Mouse(mouse);
                     }
                     if !inputs[LeftMouse] {
                         if let StickEnd::Key(key2) = *end {
                             if *key == key2 {
                                 self.saved.toggle_locked(*key);
                             } else {
-                                self.saved.add_stick(*key, key2);
+                                self.saved.add_stick(*key, key2, self.kind);
                             }
                         }
                         self.action = Action::None;
@@ -130,10 +380,8 @@ impl State {
                 }
                 Action::Deleting(last) => {
                     if inputs[RightMouse] {
-                        self.saved
-                            .remove_sticks((*last).as_dvec2(), mouse.as_dvec2());
-                        self.saved
-                            .remove_points((*last).as_dvec2(), mouse.as_dvec2(), RADIUS);
+                        self.saved.remove_sticks(*last, mouse);
+                        self.saved.remove_points(*last, mouse, RADIUS);
                         *last = mouse;
                     } else {
                         self.action = Action::None;
@@ -144,27 +392,23 @@ impl State {
                         if inputs[RightMouse] {
                             self.action = Action::Panning;
                         } else {
-                            if inputs[LeftMouse] {
-                                if self
-                                    .saved
-                                    .get_point(mouse.as_dvec2(), RADIUS * 2.)
-                                    .is_none()
-                                {
-                                    self.saved.add_point(mouse.as_dvec2());
-                                }
+                            if inputs[LeftMouse]
+                                && self.saved.get_point(mouse, RADIUS * 2.).is_none()
+                            {
+                                self.saved.add_point(mouse);
                             }
-                            if let Some(key2) = self.saved.get_point(mouse.as_dvec2(), RADIUS) {
+                            if let Some(key2) = self.saved.get_point(mouse, RADIUS) {
                                 if let Some((key, _)) = selected {
                                     if *key != key2 {
-                                        self.saved.add_stick(*key, key2);
+                                        self.saved.add_stick(*key, key2, self.kind);
                                         *key = key2;
                                     }
                                 } else {
-                                    *selected = Some((key2, inputs.mouse_position()));
+                                    *selected = Some((key2, mouse));
                                 }
                             }
-                            if let Some((_, mouse)) = selected {
-                                *mouse = inputs.mouse_position();
+                            if let Some((_, end)) = selected {
+                                *end = mouse;
                             }
                         }
                     } else {
@@ -178,7 +422,7 @@ impl State {
                         if inputs[Alternate] {
                             self.action = Action::Panning;
                         } else {
-                            if let Some(key) = self.saved.get_point(mouse.as_dvec2(), RADIUS) {
+                            if let Some(key) = self.saved.get_point(mouse, RADIUS) {
                                 self.action = Action::CreatingStick(key, StickEnd::Key(key));
                             } else {
                                 self.action = Action::CreatingPoint;
@@ -192,24 +436,264 @@ impl State {
             }
         }
 
+        if inputs[ToggleStress] && !inputs.last(ToggleStress) {
+            self.stress = !self.stress;
+        }
+
+        if inputs[ToggleSmooth] && !inputs.last(ToggleSmooth) {
+            self.smooth = !self.smooth;
+        }
+
+        if inputs[SwitchTheme] && !inputs.last(SwitchTheme) {
+            self.theme = self.theme.next();
+        }
+
+        if inputs[ToggleHud] && !inputs.last(ToggleHud) {
+            self.hud = !self.hud;
+        }
+
+        if inputs[TogglePlot] && !inputs.last(TogglePlot) {
+            self.plot = !self.plot;
+            self.history.clear();
+        }
+
+        if inputs[ToggleVelocities] && !inputs.last(ToggleVelocities) {
+            self.velocities = !self.velocities;
+        }
+
+        // deleted points' keys can be reused by new points, which shouldn't inherit their trails
+        let ropes = if self.simulating {
+            &self.active
+        } else {
+            &self.saved
+        };
+        self.trails.retain(|&key, _| ropes.contains(key));
+
+        if inputs[Trace] && !inputs.last(Trace) {
+            let mouse = self.to_world(inputs.mouse_position());
+            if let Some(key) = ropes.get_point(mouse, RADIUS) {
+                if self.trails.remove(&key).is_none() {
+                    self.trails.insert(key, VecDeque::new());
+                }
+            }
+        }
+
         if inputs[ToggleSimulating] && !inputs.last(ToggleSimulating) {
             self.simulating = !self.simulating;
             if self.simulating {
                 self.accumulator = 0.;
                 self.active = self.saved.clone();
+                self.trails.values_mut().for_each(VecDeque::clear);
+                self.history.clear();
+                self.active.set_parallel(self.parallel);
                 self.active.tick();
             }
             self.action.reset();
         }
+
+        for (input, factor) in [(ZoomIn, ZOOM_STEP), (ZoomOut, 1. / ZOOM_STEP)] {
+            if inputs[input] && !inputs.last(input) {
+                self.zoom_at(inputs.mouse_position(), self.zoom * factor);
+            }
+        }
+
+        if inputs[Fit] && !inputs.last(Fit) {
+            self.fit(inputs.window_size().as_dvec2());
+        }
+
+        if inputs[ToggleFollow] && !inputs.last(ToggleFollow) {
+            let ropes = if self.simulating {
+                &self.active
+            } else {
+                &self.saved
+            };
+            let mouse = self.to_world(inputs.mouse_position());
+            self.follow = match (self.follow, ropes.get_point(mouse, RADIUS)) {
+                (Follow::None, Some(key)) => Follow::Point(key),
+                (Follow::None, None) => Follow::Centre,
+                _ => Follow::None,
+            };
+        }
+
+        if self.simulating {
+            let t = self.accumulator / TICK_DURATION;
+            let target = match self.follow {
+                Follow::Point(key) if self.active.contains(key) => {
+                    Some(self.active.get_position(key, t))
+                }
+                Follow::Centre => self.active.centre(t),
+                _ => None,
+            };
+            if let Some(target) = target {
+                self.camera = target - inputs.window_size().as_dvec2() / self.zoom / 2.;
+            }
+        }
+    }
+
+    fn to_world(&self, screen: IVec2) -> DVec2 {
+        screen.as_dvec2() / self.zoom + self.camera
+    }
+
+    // zooms keeping whatever is at the position on screen there
+    fn zoom_at(&mut self, screen: IVec2, zoom: f64) {
+        let world = self.to_world(screen);
+        self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        self.camera = world - screen.as_dvec2() / self.zoom;
+    }
+
+    // pans and zooms to fit every point in the window, going back to the start if there are none
+    fn fit(&mut self, window: DVec2) {
+        let ropes = if self.simulating {
+            &self.active
+        } else {
+            &self.saved
+        };
+        let t = self.accumulator / TICK_DURATION;
+        let bounds = ropes.get_points(t).fold(None, |bounds, (_, position, _)| {
+            Some(match bounds {
+                Some((min, max)) => (position.min(min), position.max(max)),
+                None => (position, position),
+            })
+        });
+        self.follow = Follow::None;
+        match bounds {
+            Some((min, max)) => {
+                let size = max - min + DVec2::splat(FIT_MARGIN * 2.);
+                self.zoom = (window / size).min_element().clamp(MIN_ZOOM, MAX_ZOOM);
+                self.camera = (min + max) / 2. - window / self.zoom / 2.;
+            }
+            None => {
+                self.camera = DVec2::ZERO;
+                self.zoom = 1.;
+            }
+        }
+    }
+
+    fn cycle_wind(&mut self) {
+        let wind = self
+            .saved
+            .get_fields()
+            .find_map(|(key, field)| match *field {
+                Field::Wind { velocity, .. } => Some((key, velocity.x)),
+                _ => None,
+            });
+        let next = match wind {
+            Some((key, speed)) => {
+                self.saved.remove_field(key);
+                WINDS.iter().skip_while(|&&(s, _)| s != speed).nth(1)
+            }
+            None => WINDS.first(),
+        };
+        if let Some(&(speed, turbulence)) = next {
+            self.saved.add_field(Field::Wind {
+                velocity: DVec2::new(speed, 0.),
+                turbulence,
+            });
+        }
+    }
+
+    // places an attractor at the mouse, or turns the field already there into a repulsor, then a
+    // vortex, then removes it
+    fn cycle_field(&mut self, mouse: DVec2) {
+        let near = self
+            .saved
+            .get_fields()
+            .find(|(_, field)| {
+                field
+                    .centre()
+                    .is_some_and(|centre| centre.distance(mouse) < RADIUS * 2.)
+            })
+            .map(|(key, field)| (key, *field));
+        let next = match near {
+            Some((key, field)) => {
+                self.saved.remove_field(key);
+                match field {
+                    Field::Radial {
+                        centre, strength, ..
+                    } if strength > 0. => Some(Field::Radial {
+                        centre,
+                        radius: FIELD_RADIUS,
+                        strength: -FIELD_STRENGTH,
+                    }),
+                    Field::Radial { centre, .. } => Some(Field::Vortex {
+                        centre,
+                        radius: FIELD_RADIUS,
+                        strength: FIELD_STRENGTH,
+                    }),
+                    _ => None,
+                }
+            }
+            None => Some(Field::Radial {
+                centre: mouse,
+                radius: FIELD_RADIUS,
+                strength: FIELD_STRENGTH,
+            }),
+        };
+        if let Some(field) = next {
+            self.saved.add_field(field);
+        }
+    }
+
+    // adds a corner at the mouse to the water being placed, filling it once its first corner is
+    // chosen again, or removes the water under the mouse if nothing is being placed
+    fn pour(&mut self, mouse: DVec2) {
+        match &mut self.action {
+            Action::Pouring(polygon) => {
+                if polygon[0].distance(mouse) < RADIUS {
+                    let polygon = mem::take(polygon);
+                    self.saved.add_water(polygon);
+                    self.action = Action::None;
+                } else {
+                    polygon.push(mouse);
+                }
+            }
+            Action::None if !self.saved.remove_water(mouse) => {
+                self.action = Action::Pouring(vec![mouse]);
+            }
+            _ => (),
+        }
+    }
+
+    // adds the point under the mouse to the joint being placed, creating the joint once it has
+    // all of its points
+    fn place(&mut self, joint: Joint, mouse: DVec2) {
+        let key = match self.saved.get_point(mouse, RADIUS) {
+            Some(key) => key,
+            None => {
+                if let Action::Placing(..) = self.action {
+                    self.action = Action::None;
+                }
+                return;
+            }
+        };
+        match &mut self.action {
+            Action::Placing(placing, keys) if *placing == joint => keys.push(key),
+            Action::Placing(..) | Action::None => self.action = Action::Placing(joint, vec![key]),
+            _ => return,
+        }
+        if let Action::Placing(_, keys) = &self.action {
+            match (joint, &keys[..]) {
+                (Joint::Pulley, &[key1, key2, key3]) => self.saved.add_pulley(key1, key2, key3),
+                (Joint::Slider, &[key1, key2, key3]) => self.saved.add_slider(key1, key2, key3),
+                (Joint::Weld, &[key1, key2]) => self.saved.weld(key1, key2),
+                (Joint::Pin, &[key1, key2]) => self.saved.add_pin(key1, key2),
+                _ => return,
+            }
+            self.action = Action::None;
+        }
     }
 }
 
 enum Action {
     CreatingPoint,
     CreatingStick(usize, StickEnd),
-    CreatingLine(Option<(usize, IVec2)>),
-    Deleting(IVec2),
+    CreatingLine(Option<(usize, DVec2)>),
+    Deleting(DVec2),
+    Placing(Joint, Vec<usize>),
+    Pouring(Vec<DVec2>),
     Panning,
+    // moving the camera to wherever the minimap is being held
+    Jumping,
     None,
 }
 
@@ -222,7 +706,35 @@ impl Action {
     }
 }
 
+// what the camera keeps in the middle of the window while simulating
+#[derive(Clone, Copy)]
+enum Follow {
+    Point(usize),
+    Centre,
+    None,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Joint {
+    // placed end, wheel, end
+    Pulley,
+    // placed sliding point, rail start, rail end
+    Slider,
+    // placed point kept, point merged into it
+    Weld,
+    // placed pair of points
+    Pin,
+}
+
 enum StickEnd {
     Key(usize),
-    Mouse(IVec2),
+    Mouse(DVec2),
+}
+
+// the value after the current one, going back to none after the last
+fn cycle<T: Copy + PartialEq>(values: &[T], current: Option<T>) -> Option<T> {
+    match current {
+        Some(current) => values.iter().skip_while(|&&v| v != current).nth(1).copied(),
+        None => values.first().copied(),
+    }
 }
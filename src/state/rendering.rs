@@ -1,21 +1,76 @@
-use super::{Action, State, StickEnd, RADIUS, TICK_DURATION};
+use super::{
+    ropes::{Field, Ropes, StickKind, Style},
+    theme::Theme,
+    Action, State, StickEnd, PLOT_LENGTH, RADIUS, TICK_DURATION,
+};
 use ggez::{
-    graphics::{self, DrawMode, DrawParam, Mesh},
-    Context, GameResult,
+    graphics::{
+        self, Color, DrawMode, DrawParam, Mesh, MeshBatch, MeshBuilder, Rect, Text, Vertex,
+    },
+    timer, Context, GameResult,
 };
-use glam::DVec2;
+use glam::{DVec2, IVec2};
 
-const STICK_WIDTH: f32 = 5.;
+const LINE_WIDTH: f32 = 2.;
+const RAIL_WIDTH: f32 = 3.;
+const ARC_SEGMENTS: usize = 12;
+// how many pieces each stick is split into when drawn smoothly
+const SPLINE_SEGMENTS: usize = 8;
+// how far from their ends smooth ropes narrow, and how narrow they get
+const TAPER_LENGTH: f64 = 40.;
+const TAPER: f64 = 0.4;
+// the strain shown at the hot end of the stress colours
+const MAX_TENSION: f64 = 0.02;
+const HEAT: [(u8, u8, u8); 4] = [(40, 60, 200), (40, 200, 120), (250, 220, 60), (240, 50, 40)];
+const LEGEND_SIZE: [f32; 2] = [200., 12.];
+const LEGEND_STEPS: usize = 40;
+const LEGEND_MARGIN: f32 = 20.;
+const TRAIL_WIDTH: f32 = 2.;
+const TRAIL_COLOUR: (u8, u8, u8) = (255, 180, 240);
+const ARROW_WIDTH: f32 = 2.;
+const ARROW_COLOUR: (u8, u8, u8) = (120, 255, 255);
+// how long velocity arrows are per pixel per second, and how long their heads are
+const ARROW_SCALE: f64 = 0.1;
+const ARROW_HEAD: f64 = 6.;
+// how far in from the top right corner the debug text starts
+const HUD_OFFSET: [f32; 2] = [260., 20.];
+const PLOT_SIZE: [f32; 2] = [320., 160.];
+const PLOT_MARGIN: f32 = 20.;
+const PLOT_BACKGROUND: (u8, u8, u8, u8) = (0, 0, 0, 120);
+const PLOT_COLOURS: [(u8, u8, u8); 3] = [(120, 255, 255), (255, 170, 80), (255, 90, 90)];
+const PLOT_NAMES: [&str; 3] = ["kinetic", "potential", "rms error"];
 
-const BACKGROUND: (u8, u8, u8) = (61, 64, 112);
-const POINT_COLOUR: (u8, u8, u8) = (255, 255, 255);
-const LOCKED_COLOUR: (u8, u8, u8) = (255, 0, 0);
-const STICK_COLOUR: (u8, u8, u8) = (203, 203, 212);
+const MOTOR_COLOUR: (u8, u8, u8) = (120, 230, 120);
+const ISOLATED_COLOUR: (u8, u8, u8) = (255, 200, 0);
+const ANGLE_COLOUR: (u8, u8, u8) = (255, 150, 90);
+const RAIL_COLOUR: (u8, u8, u8) = (120, 200, 255);
+const PLACING_COLOUR: (u8, u8, u8) = (150, 255, 150);
+const PIN_COLOUR: (u8, u8, u8) = (255, 230, 120);
+const BODY_COLOUR: (u8, u8, u8, u8) = (160, 170, 255, 90);
+const WATER_COLOUR: (u8, u8, u8, u8) = (40, 120, 255, 90);
+const ATTRACTOR_COLOUR: (u8, u8, u8) = (100, 230, 180);
+const REPULSOR_COLOUR: (u8, u8, u8) = (240, 110, 150);
+const VORTEX_COLOUR: (u8, u8, u8) = (190, 140, 255);
+const WIND_COLOUR: (u8, u8, u8) = (220, 240, 255);
+// where the wind arrow starts on screen, and how long it is per pixel per second of wind
+const WIND_ORIGIN: [f32; 2] = [40., 40.];
+const WIND_SCALE: f64 = 0.2;
+const GRID_SPACING: f64 = 100.;
+const GRID_WIDTH: f32 = 1.;
+const AXIS_WIDTH: f32 = 2.;
+const MINIMAP_SIZE: [f32; 2] = [240., 150.];
+// how far in from the bottom left corner the minimap is, leaving room for the legend below it
+const MINIMAP_OFFSET: [f32; 2] = [20., 230.];
+// the space left around everything in the minimap, as a fraction of its size
+const MINIMAP_PADDING: f64 = 0.1;
+const MINIMAP_BACKGROUND: (u8, u8, u8, u8) = (0, 0, 0, 120);
+const MINIMAP_POINT: f32 = 2.;
 
 impl State {
     pub fn render(&self, ctx: &mut Context) -> GameResult {
-        let draw_param = DrawParam::default();
-        let camera = self.camera.as_dvec2();
+        // everything in the world is drawn relative to the camera, then zoomed
+        let draw_param = DrawParam::default().scale([self.zoom as f32; 2]);
+        let camera = self.camera;
         let t = self.accumulator / TICK_DURATION;
 
         let ropes = if self.simulating {
@@ -24,62 +79,707 @@ impl State {
             &self.saved
         };
 
-        graphics::clear(ctx, BACKGROUND.into());
+        graphics::clear(ctx, self.theme.background.into());
+
+        let (width, height) = graphics::drawable_size(ctx);
+        let window = DVec2::new(width as f64, height as f64);
+        let view = window / self.zoom;
+        self.draw_grid(ctx, view, draw_param)?;
 
-        for (a, b) in ropes.get_sticks(t) {
-            let mesh = stick_mesh(ctx, a - camera, b - camera)?;
+        // faces are one mesh of their own under everything else, so they can share the texture
+        let colour = match self.texture {
+            Some(_) => Color::WHITE,
+            None => self.theme.cloth.into(),
+        };
+        let colour = linear(colour);
+        let (mut vertices, mut indices) = (Vec::new(), Vec::new());
+        for face in ropes.get_faces(t) {
+            let start = vertices.len() as u32;
+            for i in 1..face.len() as u32 - 1 {
+                indices.extend([start, start + i, start + i + 1]);
+            }
+            vertices.extend(face.iter().map(|&(position, uv)| Vertex {
+                pos: as_point(position - camera),
+                uv: as_point(uv),
+                color: colour,
+            }));
+        }
+        if !indices.is_empty() {
+            let mesh = Mesh::from_raw(ctx, &vertices, &indices, self.texture.clone())?;
             graphics::draw(ctx, &mesh, draw_param)?;
         }
 
+        // everything drawn under the points goes into one mesh, in the order it should be layered
+        let mut scene = Batch::new();
+
+        for polygon in ropes.get_waters() {
+            let points: Vec<[f32; 2]> = polygon.iter().map(|&p| as_point(p - camera)).collect();
+            scene.polygon(&points, WATER_COLOUR.into())?;
+        }
+
+        if let Action::Pouring(polygon) = &self.action {
+            let points: Vec<[f32; 2]> = polygon.iter().map(|&p| as_point(p - camera)).collect();
+            if points.len() > 1 {
+                scene.line(&points, LINE_WIDTH, PLACING_COLOUR.into())?;
+            }
+            scene.circle(
+                DrawMode::stroke(2.),
+                points[0],
+                RADIUS as f32,
+                PLACING_COLOUR.into(),
+            )?;
+        }
+
+        // drawn over the scene without zooming
+        let mut screen = Batch::new();
+
+        for (_, field) in ropes.get_fields() {
+            let (centre, radius, colour) = match *field {
+                Field::Wind { velocity, .. } => {
+                    let [x, y] = WIND_ORIGIN;
+                    let end = DVec2::new(x as f64, y as f64) + velocity * WIND_SCALE;
+                    if end != DVec2::new(x as f64, y as f64) {
+                        let points = [WIND_ORIGIN, as_point(end)];
+                        screen.line(&points, LINE_WIDTH, WIND_COLOUR.into())?;
+                    }
+                    continue;
+                }
+                Field::Radial {
+                    centre,
+                    radius,
+                    strength,
+                } if strength >= 0. => (centre, radius, ATTRACTOR_COLOUR),
+                Field::Radial { centre, radius, .. } => (centre, radius, REPULSOR_COLOUR),
+                Field::Vortex { centre, radius, .. } => (centre, radius, VORTEX_COLOUR),
+            };
+            let centre = as_point(centre - camera);
+            scene.circle(DrawMode::stroke(2.), centre, radius as f32, colour.into())?;
+            scene.circle(DrawMode::fill(), centre, RADIUS as f32 / 2., colour.into())?;
+        }
+
+        for positions in ropes.get_bodies(t) {
+            let hull = convex_hull(positions);
+            if hull.len() >= 3 {
+                let points: Vec<[f32; 2]> = hull.iter().map(|&p| as_point(p - camera)).collect();
+                scene.polygon(&points, BODY_COLOUR.into())?;
+            }
+        }
+
+        // the stress colours are per stick, so they are always drawn straight
+        if self.smooth && !self.stress {
+            for (positions, closed, kind, style) in ropes.get_chains(t) {
+                let positions: Vec<DVec2> = positions.iter().map(|&p| p - camera).collect();
+                let (width, colour) = stick_style(&self.theme, kind, style);
+                scene.stroke(&catmull_rom(&positions, closed), width, colour, closed)?;
+            }
+        } else {
+            for (a, b, kind, style, motorised, tension) in ropes.get_sticks(t) {
+                let points = [as_point(a - camera), as_point(b - camera)];
+                let (width, colour) = stick_style(&self.theme, kind, style);
+                let colour = if self.stress {
+                    heat(tension.abs() / MAX_TENSION)
+                } else if motorised {
+                    MOTOR_COLOUR.into()
+                } else {
+                    colour
+                };
+                scene.line(&points, width, colour)?;
+            }
+        }
+
+        for (a, b) in ropes.get_sliders(t) {
+            let points = [as_point(a - camera), as_point(b - camera)];
+            scene.line(&points, RAIL_WIDTH, RAIL_COLOUR.into())?;
+        }
+
+        for (wheel, end1, end2) in ropes.get_pulleys(t) {
+            let points = [end1, wheel, end2].map(|position| as_point(position - camera));
+            let (width, colour) = stick_style(&self.theme, StickKind::Rope, Style::default());
+            scene.line(&points, width, colour)?;
+            scene.circle(
+                DrawMode::stroke(width),
+                as_point(wheel - camera),
+                RADIUS as f32 * 1.5,
+                colour,
+            )?;
+        }
+
+        for (pivot, end1, end2, stiffness) in ropes.get_angles(t) {
+            let (arm1, arm2) = (end1 - pivot, end2 - pivot);
+            let start = arm1.y.atan2(arm1.x);
+            let sweep = arm1.perp_dot(arm2).atan2(arm1.dot(arm2));
+            if sweep.abs() > 0.05 {
+                let points: Vec<[f32; 2]> = (0..=ARC_SEGMENTS)
+                    .map(|i| {
+                        let angle = start + sweep * i as f64 / ARC_SEGMENTS as f64;
+                        as_point(pivot - camera + DVec2::from_angle(angle) * RADIUS * 2.)
+                    })
+                    .collect();
+                let width = 2. + 6. * stiffness as f32;
+                scene.line(&points, width, ANGLE_COLOUR.into())?;
+            }
+        }
+
         if let Action::CreatingStick(start, end) = &self.action {
             let a = ropes.get_position(*start, t) - camera;
             let b = match end {
                 StickEnd::Key(key) => ropes.get_position(*key, t) - camera,
-                StickEnd::Mouse(pos) => (*pos).as_dvec2(),
+                StickEnd::Mouse(pos) => *pos - camera,
             };
             if (a - b).length_squared() >= RADIUS * RADIUS {
-                let mesh = stick_mesh(ctx, a, b)?;
-                graphics::draw(ctx, &mesh, draw_param)?;
+                let (width, colour) = stick_style(&self.theme, self.kind, Style::default());
+                scene.line(&[as_point(a), as_point(b)], width, colour)?;
             }
         }
 
         if let Action::CreatingLine(Some((key, mouse))) = self.action {
             let a = ropes.get_position(key, t) - camera;
-            let b = mouse.as_dvec2();
+            let b = mouse - camera;
             if (a - b).length_squared() >= RADIUS * RADIUS {
-                let mesh = stick_mesh(ctx, a, b)?;
-                graphics::draw(ctx, &mesh, draw_param)?;
+                let (width, colour) = stick_style(&self.theme, self.kind, Style::default());
+                scene.line(&[as_point(a), as_point(b)], width, colour)?;
             }
         }
 
+        if let Action::Placing(_, keys) = &self.action {
+            let points: Vec<[f32; 2]> = keys
+                .iter()
+                .map(|&key| as_point(ropes.get_position(key, t) - camera))
+                .collect();
+            for &point in points.iter() {
+                scene.circle(
+                    DrawMode::stroke(3.),
+                    point,
+                    RADIUS as f32 * 1.5,
+                    PLACING_COLOUR.into(),
+                )?;
+            }
+            if points.len() > 1 {
+                scene.line(&points, LINE_WIDTH, PLACING_COLOUR.into())?;
+            }
+        }
+
+        scene.draw(ctx, draw_param)?;
+
+        // points all share one circle, drawn as instances in a single call
         let point = Mesh::new_circle(
             ctx,
             DrawMode::fill(),
             [0., 0.],
-            RADIUS as f32,
+            RADIUS as f32 * self.theme.point_scale,
             0.4,
             (255, 255, 255).into(),
         )?;
-        for (position, locked) in ropes.get_points(t) {
-            let draw_param = draw_param
-                .color(if locked { LOCKED_COLOUR } else { POINT_COLOUR }.into())
-                .dest(as_point(position - camera));
-            graphics::draw(ctx, &point, draw_param)?;
+        let mut points = MeshBatch::new(point)?;
+        for (_, position, locked) in ropes.get_points(t) {
+            points.add(
+                DrawParam::default()
+                    .color(
+                        if locked {
+                            self.theme.locked
+                        } else {
+                            self.theme.point
+                        }
+                        .into(),
+                    )
+                    .dest(as_point(position - camera)),
+            );
+        }
+        points.draw(ctx, draw_param)?;
+
+        let mut overlay = Batch::new();
+
+        for trail in self.trails.values() {
+            for (i, pair) in trail.iter().collect::<Vec<_>>().windows(2).enumerate() {
+                if pair[0] == pair[1] {
+                    continue;
+                }
+                let points = [as_point(*pair[0] - camera), as_point(*pair[1] - camera)];
+                let mut colour: Color = TRAIL_COLOUR.into();
+                colour.a = (i + 1) as f32 / trail.len() as f32;
+                overlay.line(&points, TRAIL_WIDTH, colour)?;
+            }
+        }
+
+        if self.velocities {
+            for (position, velocity) in ropes.get_velocities(t) {
+                let tip = position + velocity * ARROW_SCALE;
+                let head = match (position - tip).try_normalize() {
+                    Some(back) if velocity.length() * ARROW_SCALE > ARROW_HEAD => back * ARROW_HEAD,
+                    _ => continue,
+                };
+                let [left, right] = [0.5, -0.5].map(|angle| DVec2::from_angle(angle).rotate(head));
+                let points = [position, tip, tip + left, tip, tip + right]
+                    .map(|point| as_point(point - camera));
+                overlay.line(&points, ARROW_WIDTH, ARROW_COLOUR.into())?;
+            }
+        }
+
+        for position in ropes.get_pins(t) {
+            overlay.circle(
+                DrawMode::stroke(2.),
+                as_point(position - camera),
+                RADIUS as f32 * 0.6,
+                PIN_COLOUR.into(),
+            )?;
+        }
+
+        for position in ropes.get_isolated() {
+            overlay.circle(
+                DrawMode::stroke(3.),
+                as_point(position - camera),
+                RADIUS as f32 * 1.5,
+                ISOLATED_COLOUR.into(),
+            )?;
+        }
+
+        overlay.draw(ctx, draw_param)?;
+        screen.draw(ctx, DrawParam::default())?;
+
+        Minimap::new(ropes, camera, view, window).draw(ctx, ropes, &self.theme, t)?;
+
+        if self.stress {
+            draw_legend(ctx, self.theme.text.into())?;
+        }
+
+        if self.hud {
+            self.draw_hud(ctx)?;
+        }
+
+        if self.plot {
+            self.draw_plot(ctx)?;
         }
 
         graphics::present(ctx)
     }
 }
 
-fn stick_mesh(ctx: &mut Context, a: DVec2, b: DVec2) -> GameResult<Mesh> {
-    Mesh::new_line(
-        ctx,
-        &[as_point(a), as_point(b)],
-        STICK_WIDTH,
-        STICK_COLOUR.into(),
+impl State {
+    // lines every so far across the world, with stronger ones through the origin
+    // keeping the same width on screen however far it is zoomed
+    fn draw_grid(&self, ctx: &mut Context, view: DVec2, draw_param: DrawParam) -> GameResult {
+        let camera = self.camera;
+        let mut grid = Batch::new();
+        let (start, end) = (
+            (camera / GRID_SPACING).floor() * GRID_SPACING,
+            camera + view,
+        );
+        let mut x = start.x;
+        while x <= end.x {
+            let (width, colour) = grid_line(&self.theme, x);
+            grid.line(
+                &[
+                    [(x - camera.x) as f32, 0.],
+                    [(x - camera.x) as f32, view.y as f32],
+                ],
+                width / self.zoom as f32,
+                colour,
+            )?;
+            x += GRID_SPACING;
+        }
+        let mut y = start.y;
+        while y <= end.y {
+            let (width, colour) = grid_line(&self.theme, y);
+            grid.line(
+                &[
+                    [0., (y - camera.y) as f32],
+                    [view.x as f32, (y - camera.y) as f32],
+                ],
+                width / self.zoom as f32,
+                colour,
+            )?;
+            y += GRID_SPACING;
+        }
+        grid.draw(ctx, draw_param)
+    }
+
+    fn draw_hud(&self, ctx: &mut Context) -> GameResult {
+        let ropes = if self.simulating {
+            &self.active
+        } else {
+            &self.saved
+        };
+        let statistics = ropes.statistics();
+        let text = Text::new(format!(
+            "fps: {:.0}\n\
+             ticks per frame: {:.2}\n\
+             tick time: {:.3} ms\n\
+             points: {}\n\
+             sticks: {}\n\
+             kinetic energy: {:.0}\n\
+             max strain: {:.3}%",
+            timer::fps(ctx),
+            self.ticks_per_frame,
+            self.seconds_per_tick * 1000.,
+            statistics.points,
+            statistics.sticks,
+            statistics.kinetic_energy,
+            statistics.max_strain * 100.,
+        ));
+        let (width, _) = graphics::drawable_size(ctx);
+        let [x, y] = HUD_OFFSET;
+        let param = DrawParam::default()
+            .dest([width - x, y])
+            .color(self.theme.text.into());
+        graphics::draw(ctx, &text, param)
+    }
+
+    // graphs each recorded value along the bottom right of the screen, scaled to fill the panel
+    fn draw_plot(&self, ctx: &mut Context) -> GameResult {
+        let (width, height) = graphics::drawable_size(ctx);
+        let [plot_width, plot_height] = PLOT_SIZE;
+        let (x, y) = (
+            width - PLOT_MARGIN - plot_width,
+            height - PLOT_MARGIN - plot_height,
+        );
+        let rect = Rect::new(x, y, plot_width, plot_height);
+        let mesh = Mesh::new_rectangle(ctx, DrawMode::fill(), rect, PLOT_BACKGROUND.into())?;
+        graphics::draw(ctx, &mesh, DrawParam::default())?;
+
+        for (series, (colour, name)) in PLOT_COLOURS.iter().zip(PLOT_NAMES).enumerate() {
+            let values: Vec<f64> = self.history.iter().map(|values| values[series]).collect();
+            let (min, max) = values
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &value| {
+                    (min.min(value), max.max(value))
+                });
+            let range = if max > min { max - min } else { 1. };
+            let points: Vec<[f32; 2]> = values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| {
+                    [
+                        x + plot_width * i as f32 / (PLOT_LENGTH - 1) as f32,
+                        y + plot_height * (1. - ((value - min) / range) as f32),
+                    ]
+                })
+                .collect();
+            if points.len() >= 2 {
+                let mesh = Mesh::new_line(ctx, &points, 1.5, (*colour).into())?;
+                graphics::draw(ctx, &mesh, DrawParam::default())?;
+            }
+
+            let label = match values.last() {
+                Some(value) => format!("{}: {:.1}", name, value),
+                None => name.to_string(),
+            };
+            let dest = [x + 4., y + 4. + 16. * series as f32];
+            let param = DrawParam::default().dest(dest).color((*colour).into());
+            graphics::draw(ctx, &Text::new(label), param)?;
+        }
+        Ok(())
+    }
+}
+
+// a bar of the stress colours along the bottom left of the screen, labelled with their strains
+fn draw_legend(ctx: &mut Context, colour: Color) -> GameResult {
+    let (_, height) = graphics::drawable_size(ctx);
+    let [width, bar_height] = LEGEND_SIZE;
+    let (x, y) = (LEGEND_MARGIN, height - LEGEND_MARGIN - bar_height);
+    let step = width / LEGEND_STEPS as f32;
+    let mut bar = MeshBuilder::new();
+    for i in 0..LEGEND_STEPS {
+        let rect = Rect::new(x + step * i as f32, y, step, bar_height);
+        let colour = heat(i as f64 / (LEGEND_STEPS - 1) as f64);
+        bar.rectangle(DrawMode::fill(), rect, colour)?;
+    }
+    let mesh = bar.build(ctx)?;
+    graphics::draw(ctx, &mesh, DrawParam::default())?;
+    for (text, offset) in [
+        ("0%".to_string(), 0.),
+        (format!("{}%", MAX_TENSION * 100.), width),
+    ] {
+        let text = Text::new(text);
+        let text_width = text.width(ctx);
+        let dest = [x + offset - text_width * offset / width, y - 20.];
+        let param = DrawParam::default().dest(dest).color(colour);
+        graphics::draw(ctx, &text, param)?;
+    }
+    Ok(())
+}
+
+// the colour along the stress colours, from 0 for none to 1 for the most shown
+fn heat(value: f64) -> Color {
+    let position = value.clamp(0., 1.) * (HEAT.len() - 1) as f64;
+    let i = (position as usize).min(HEAT.len() - 2);
+    let (from, to) = (HEAT[i], HEAT[i + 1]);
+    let t = (position - i as f64) as f32;
+    let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t) / 255.;
+    Color::new(
+        lerp(from.0, to.0),
+        lerp(from.1, to.1),
+        lerp(from.2, to.2),
+        1.,
     )
 }
 
+// the width and colour of the grid line at a world coordinate
+fn grid_line(theme: &Theme, coordinate: f64) -> (f32, Color) {
+    if coordinate == 0. {
+        (AXIS_WIDTH, theme.axes.into())
+    } else {
+        (GRID_WIDTH, theme.grid.into())
+    }
+}
+
+// where the minimap is on screen and how the world is fitted into it
+pub(super) struct Minimap {
+    rect: Rect,
+    min: DVec2,
+    scale: f64,
+    // the corners of the part of the world in the window
+    view: (DVec2, DVec2),
+}
+
+impl Minimap {
+    // fits every point and the view into the minimap, keeping their proportions
+    pub(super) fn new(ropes: &Ropes, camera: DVec2, view: DVec2, window: DVec2) -> Self {
+        let view = (camera, camera + view);
+        let (min, max) = ropes
+            .get_points(1.)
+            .fold(view, |(min, max), (_, position, _)| {
+                (min.min(position), max.max(position))
+            });
+        let padding = (max - min) * MINIMAP_PADDING;
+        let (min, max) = (min - padding, max + padding);
+
+        let [width, height] = MINIMAP_SIZE;
+        let [x, y] = MINIMAP_OFFSET;
+        let rect = Rect::new(x, window.y as f32 - y, width, height);
+        let span = (max - min).max(DVec2::ONE);
+        let scale = (width as f64 / span.x).min(height as f64 / span.y);
+        // centred in whichever direction has room to spare
+        let size = DVec2::new(width as f64, height as f64) / scale;
+        let min = min - (size - (max - min)) / 2.;
+        Self {
+            rect,
+            min,
+            scale,
+            view,
+        }
+    }
+
+    pub(super) fn contains(&self, screen: IVec2) -> bool {
+        self.rect.contains([screen.x as f32, screen.y as f32])
+    }
+
+    pub(super) fn to_world(&self, screen: IVec2) -> DVec2 {
+        let corner = DVec2::new(self.rect.x as f64, self.rect.y as f64);
+        (screen.as_dvec2() - corner) / self.scale + self.min
+    }
+
+    fn to_screen(&self, position: DVec2) -> [f32; 2] {
+        let corner = DVec2::new(self.rect.x as f64, self.rect.y as f64);
+        as_point(corner + (position - self.min) * self.scale)
+    }
+
+    fn draw(&self, ctx: &mut Context, ropes: &Ropes, theme: &Theme, t: f64) -> GameResult {
+        let mut minimap = Batch::new();
+        minimap.rectangle(DrawMode::fill(), self.rect, MINIMAP_BACKGROUND.into())?;
+        for (_, position, locked) in ropes.get_points(t) {
+            let [x, y] = self.to_screen(position);
+            let size = MINIMAP_POINT;
+            let rect = Rect::new(x - size / 2., y - size / 2., size, size);
+            let colour = if locked { theme.locked } else { theme.point };
+            minimap.rectangle(DrawMode::fill(), rect, colour.into())?;
+        }
+        let ([x1, y1], [x2, y2]) = (self.to_screen(self.view.0), self.to_screen(self.view.1));
+        let view = Rect::new(x1, y1, x2 - x1, y2 - y1);
+        minimap.rectangle(DrawMode::stroke(1.), view, theme.text.into())?;
+        minimap.draw(ctx, DrawParam::default())
+    }
+}
+
+// the width and colour of a stick, from its own style where it has one and the theme otherwise
+fn stick_style(theme: &Theme, kind: StickKind, style: Style) -> (f32, Color) {
+    let (width, colour) = match kind {
+        StickKind::Rod => (theme.stick_width, theme.stick),
+        StickKind::Rope => (theme.rope_width, theme.rope),
+    };
+    (
+        style.width.unwrap_or(width),
+        style.colour.unwrap_or(colour).into(),
+    )
+}
+
+// shapes gathered into a single mesh, so drawing a whole layer costs one draw call however
+// many sticks it holds
+struct Batch {
+    builder: MeshBuilder,
+    empty: bool,
+}
+
+impl Batch {
+    fn new() -> Self {
+        Self {
+            builder: MeshBuilder::new(),
+            empty: true,
+        }
+    }
+
+    fn line(&mut self, points: &[[f32; 2]], width: f32, colour: Color) -> GameResult {
+        self.builder.line(points, width, colour)?;
+        self.empty = false;
+        Ok(())
+    }
+
+    fn circle(
+        &mut self,
+        mode: DrawMode,
+        point: [f32; 2],
+        radius: f32,
+        colour: Color,
+    ) -> GameResult {
+        self.builder.circle(mode, point, radius, 0.4, colour)?;
+        self.empty = false;
+        Ok(())
+    }
+
+    fn polygon(&mut self, points: &[[f32; 2]], colour: Color) -> GameResult {
+        self.builder.polygon(DrawMode::fill(), points, colour)?;
+        self.empty = false;
+        Ok(())
+    }
+
+    fn rectangle(&mut self, mode: DrawMode, rect: Rect, colour: Color) -> GameResult {
+        self.builder.rectangle(mode, rect, colour)?;
+        self.empty = false;
+        Ok(())
+    }
+
+    // a strip of triangles along the curve, narrowing towards open ends, which are rounded off
+    fn stroke(&mut self, curve: &[DVec2], width: f32, colour: Color, closed: bool) -> GameResult {
+        let mut distances = vec![0.];
+        for pair in curve.windows(2) {
+            distances.push(distances[distances.len() - 1] + pair[0].distance(pair[1]));
+        }
+        let total = distances[distances.len() - 1];
+
+        let mut normal = DVec2::Y;
+        let mut sides = Vec::with_capacity(curve.len());
+        for (i, &point) in curve.iter().enumerate() {
+            let (before, after) = (
+                curve[i.saturating_sub(1)],
+                curve[(i + 1).min(curve.len() - 1)],
+            );
+            if let Some(direction) = (after - before).try_normalize() {
+                normal = direction.perp();
+            }
+            let taper = if closed {
+                1.
+            } else {
+                let distance = distances[i].min(total - distances[i]);
+                TAPER + (1. - TAPER) * (distance / TAPER_LENGTH).min(1.)
+            };
+            let half = normal * width as f64 / 2. * taper;
+            sides.push((as_point(point + half), as_point(point - half)));
+        }
+
+        let mut triangles = Vec::with_capacity(sides.len() * 6);
+        for pair in sides.windows(2) {
+            let ((left1, right1), (left2, right2)) = (pair[0], pair[1]);
+            triangles.extend([left1, right1, left2, right1, right2, left2]);
+        }
+        if !triangles.is_empty() {
+            self.builder.triangles(&triangles, colour)?;
+            self.empty = false;
+        }
+
+        if !closed {
+            let radius = width * TAPER as f32 / 2.;
+            for end in [curve[0], curve[curve.len() - 1]] {
+                self.circle(DrawMode::fill(), as_point(end), radius, colour)?;
+            }
+        }
+        Ok(())
+    }
+
+    // building an empty mesh fails, so there is nothing to draw until a shape has been added
+    fn draw(&self, ctx: &mut Context, param: DrawParam) -> GameResult {
+        if self.empty {
+            return Ok(());
+        }
+        let mesh = self.builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, param)
+    }
+}
+
+// points along a catmull-rom spline through the positions, which passes through each of them
+fn catmull_rom(positions: &[DVec2], closed: bool) -> Vec<DVec2> {
+    let n = positions.len();
+    let segments = if closed { n } else { n - 1 };
+    let at = |i: isize| {
+        if closed {
+            positions[i.rem_euclid(n as isize) as usize]
+        } else if i < 0 {
+            // past the ends, carrying on straight
+            positions[0] * 2. - positions[1]
+        } else if i as usize >= n {
+            positions[n - 1] * 2. - positions[n - 2]
+        } else {
+            positions[i as usize]
+        }
+    };
+
+    let mut curve = Vec::with_capacity(segments * SPLINE_SEGMENTS + 1);
+    for i in 0..segments as isize {
+        let [p0, p1, p2, p3] = [at(i - 1), at(i), at(i + 1), at(i + 2)];
+        for step in 0..SPLINE_SEGMENTS {
+            let s = step as f64 / SPLINE_SEGMENTS as f64;
+            curve.push(
+                (p1 * 2.
+                    + (p2 - p0) * s
+                    + (p0 * 2. - p1 * 5. + p2 * 4. - p3) * s * s
+                    + (p1 * 3. - p0 - p2 * 3. + p3) * s * s * s)
+                    / 2.,
+            );
+        }
+    }
+    curve.push(at(segments as isize));
+    curve
+}
+
+// the smallest convex polygon around the points, anticlockwise and without collinear corners
+fn convex_hull(mut points: Vec<DVec2>) -> Vec<DVec2> {
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    let mut hull: Vec<DVec2> = Vec::new();
+    for pass in 0..2 {
+        let start = hull.len();
+        for &point in points.iter() {
+            while hull.len() >= start + 2 {
+                let (a, b) = (hull[hull.len() - 2], hull[hull.len() - 1]);
+                if (b - a).perp_dot(point - a) > 0. {
+                    break;
+                }
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        hull.pop();
+        if pass == 0 {
+            points.reverse();
+        }
+    }
+    hull
+}
+
+// raw vertices take their colours without the conversion from srgb that meshes otherwise get
+fn linear(colour: Color) -> [f32; 4] {
+    let convert = |c: f32| {
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    [
+        convert(colour.r),
+        convert(colour.g),
+        convert(colour.b),
+        colour.a,
+    ]
+}
+
 fn as_point(v: DVec2) -> [f32; 2] {
     [v.x as f32, v.y as f32]
 }
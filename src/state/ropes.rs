@@ -1,152 +1,1134 @@
+mod angle;
+mod body;
+mod colouring;
+mod face;
+mod field;
+mod grid;
+mod motor;
+mod pin;
+mod pulley;
+mod slider;
+mod water;
+
 use super::TICK_DURATION;
+use angle::Angle;
+use body::Body;
+use face::Face;
+pub use field::Field;
 use glam::DVec2;
-use rand::seq::IteratorRandom;
+use grid::Grid;
+pub use motor::Motor;
+use pin::Pin;
+use pulley::Pulley;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use slab::Slab;
+use slider::Slider;
 use std::{
-    collections::HashSet,
-    hash::{Hash, Hasher},
+    collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
+    fs, io, mem,
+    path::Path,
 };
+use water::Water;
 
 const GRAVITY: f64 = 1000.;
 const REPETITIONS: u8 = 8;
+const TOLERANCE: f64 = 1e-12;
+const MAX_SPEED: f64 = 100000.;
+// the most of a point's speed across the air one stick can take away in a tick
+const MAX_DRAG: f64 = 0.25;
 
 const TICK_GRAVITY: f64 = GRAVITY * TICK_DURATION * TICK_DURATION;
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Ropes {
     points: Slab<Point>,
-    sticks: HashSet<Stick>,
+    sticks: Slab<Stick>,
+    #[serde(default)]
+    angles: Slab<Angle>,
+    #[serde(default)]
+    pulleys: Slab<Pulley>,
+    #[serde(default)]
+    sliders: Slab<Slider>,
+    #[serde(default)]
+    pins: Slab<Pin>,
+    #[serde(default)]
+    bodies: Slab<Body>,
+    #[serde(default)]
+    fields: Slab<Field>,
+    #[serde(default)]
+    waters: Slab<Water>,
+    #[serde(default)]
+    faces: Slab<Face>,
+    #[serde(default)]
+    drag: f64,
+    #[serde(skip)]
+    time: f64,
+    #[serde(skip)]
+    winding: f64,
+    #[serde(skip)]
+    point_grid: Grid<usize>,
+    #[serde(skip)]
+    stick_grid: Grid<usize>,
+    #[serde(skip, default = "StdRng::from_entropy")]
+    rng: StdRng,
+    #[serde(skip)]
+    parallel: bool,
+    #[serde(skip)]
+    colours: Option<Vec<Vec<usize>>>,
 }
 
 impl Ropes {
     pub fn new() -> Self {
         Self {
             points: Slab::new(),
-            sticks: HashSet::new(),
+            sticks: Slab::new(),
+            angles: Slab::new(),
+            pulleys: Slab::new(),
+            sliders: Slab::new(),
+            pins: Slab::new(),
+            bodies: Slab::new(),
+            fields: Slab::new(),
+            waters: Slab::new(),
+            faces: Slab::new(),
+            drag: 0.,
+            time: 0.,
+            winding: 0.,
+            point_grid: Grid::new(),
+            stick_grid: Grid::new(),
+            rng: StdRng::from_entropy(),
+            parallel: false,
+            colours: None,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = fs::File::open(path)?;
+        let mut ropes: Self = serde_json::from_reader(io::BufReader::new(file))?;
+        if !ropes.valid() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the scene refers to points or sticks that are not in it",
+            ));
+        }
+        for (key, Stick { points, .. }) in ropes.sticks.iter() {
+            ropes.points[points.0].sticks.push(key);
+            ropes.points[points.1].sticks.push(key);
+        }
+        for (key, Angle { points, .. }) in ropes.angles.iter() {
+            ropes.points[points.1].angles.push(key);
+        }
+        for (key, Body { points, .. }) in ropes.bodies.iter() {
+            for &point in points.iter() {
+                ropes.points[point].body = Some(key);
+            }
         }
+        ropes.index();
+        Ok(ropes)
+    }
+
+    // whether everything refers only to points and sticks that exist, sticks join two different
+    // points, angles are between the sticks joining their pivot to their ends, no point is in two
+    // bodies and every water is a finite polygon, which a scene written by hand or by an older
+    // version might not
+    fn valid(&self) -> bool {
+        let exist = |keys: &[usize]| keys.iter().all(|&key| self.points.contains(key));
+        let joins = |stick: usize, key1: usize, key2: usize| {
+            self.sticks
+                .get(stick)
+                .is_some_and(|stick| stick.points == (key1, key2) || stick.points == (key2, key1))
+        };
+        let mut bodies = vec![false; self.points.capacity()];
+        self.sticks.iter().all(|(_, stick)| {
+            stick.points.0 != stick.points.1 && exist(&[stick.points.0, stick.points.1])
+        }) && self.angles.iter().all(|(_, angle)| {
+            let (end1, pivot, end2) = angle.points;
+            exist(&[end1, pivot, end2])
+                && joins(angle.sticks.0, pivot, end1)
+                && joins(angle.sticks.1, pivot, end2)
+        }) && self.pulleys.iter().all(|(_, pulley)| {
+            let (end1, wheel, end2) = pulley.points;
+            exist(&[end1, wheel, end2])
+        }) && self
+            .sliders
+            .iter()
+            .all(|(_, slider)| exist(&[slider.point, slider.rail.0, slider.rail.1]))
+            && self
+                .pins
+                .iter()
+                .all(|(_, pin)| exist(&[pin.points.0, pin.points.1]))
+            && self.bodies.iter().all(|(_, body)| {
+                body.valid()
+                    && exist(&body.points)
+                    && body
+                        .points
+                        .iter()
+                        .all(|&key| !mem::replace(&mut bodies[key], true))
+            })
+            && self
+                .faces
+                .iter()
+                .all(|(_, face)| face.valid() && exist(&face.points))
+            && self.waters.iter().all(|(_, water)| water.valid())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let file = fs::File::create(path)?;
+        Ok(serde_json::to_writer_pretty(
+            io::BufWriter::new(file),
+            self,
+        )?)
+    }
+
+    pub fn seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    pub fn set_parallel(&mut self, parallel: bool) {
+        self.parallel = parallel;
+    }
+
+    // winds every winch in (-1) or out (1), or holds them (0), until set again
+    pub fn set_winding(&mut self, winding: f64) {
+        self.winding = winding.clamp(-1., 1.);
     }
 
     pub fn tick(&mut self) {
+        for (_, stick) in self.sticks.iter_mut() {
+            if let Some(motor) = stick.motor {
+                stick.length = motor.drive(stick.length, self.time, TICK_DURATION, self.winding);
+            }
+        }
+        self.apply_drag();
+        let (fields, waters) = (&self.fields, &self.waters);
         for (_, point) in self.points.iter_mut() {
-            if !point.locked {
+            if !point.locked && !point.isolated {
                 let last = point.position;
-                point.position += point.position - point.last_position;
-                point.last_position = last;
+                let mut velocity = point.position - point.last_position;
+                let mut acceleration = DVec2::ZERO;
+                for (_, field) in fields.iter() {
+                    acceleration += field.acceleration(last);
+                }
+                for (_, water) in waters.iter() {
+                    if water.contains(last) {
+                        velocity *= (1. - water.drag * TICK_DURATION).max(0.);
+                        acceleration.y -= water.buoyancy;
+                    }
+                }
+                point.position += velocity + acceleration * TICK_DURATION * TICK_DURATION;
                 point.position.y += TICK_GRAVITY;
+                point.last_position = last;
+            }
+        }
+        self.time += TICK_DURATION;
+        self.isolate_unstable();
+
+        if self.parallel {
+            self.solve_parallel();
+        } else {
+            let mut order: Vec<usize> = self.sticks.iter().map(|(key, _)| key).collect();
+            for _ in 0..REPETITIONS {
+                order.shuffle(&mut self.rng);
+                for &key in order.iter() {
+                    let positions = self.sticks[key].solve(&self.points);
+                    self.apply(key, positions);
+                }
+                self.solve_angles();
+                self.solve_joints();
+                self.solve_bodies();
+            }
+        }
+
+        for (_, stick) in self.sticks.iter_mut() {
+            stick.tension = stick.strain(&self.points);
+        }
+        self.isolate_unstable();
+        self.index();
+    }
+
+    // slows each stick's movement across the air, through its ends, more for longer sticks
+    fn apply_drag(&mut self) {
+        if self.drag == 0. {
+            return;
+        }
+        let mut changes = vec![DVec2::ZERO; self.points.capacity()];
+        for (_, stick) in self.sticks.iter() {
+            let (point1, point2) = (&self.points[stick.points.0], &self.points[stick.points.1]);
+            let along = point1.position - point2.position;
+            let normal = match along.perp().try_normalize() {
+                Some(normal) => normal,
+                None => continue,
+            };
+            let centre = (point1.position + point2.position) / 2.;
+            let air = self.fields.iter().fold(DVec2::ZERO, |air, (_, field)| {
+                air + field.air(centre, self.time)
+            });
+            let velocity = (point1.position - point1.last_position + point2.position
+                - point2.last_position)
+                / 2.;
+            let relative = velocity - air * TICK_DURATION;
+            let amount = (self.drag * along.length() * TICK_DURATION).min(MAX_DRAG);
+            let change = -normal * relative.dot(normal) * amount;
+            changes[stick.points.0] += change;
+            changes[stick.points.1] += change;
+        }
+        for (key, point) in self.points.iter_mut() {
+            if !point.locked && !point.isolated {
+                point.position += changes[key];
+            }
+        }
+    }
+
+    // detaches points that have become non-finite or are moving impossibly fast, freezing them
+    // where they last were so that they cannot spread through the sticks
+    fn isolate_unstable(&mut self) {
+        let unstable: Vec<usize> = self
+            .points
+            .iter()
+            .filter(|(_, point)| {
+                let velocity = point.position - point.last_position;
+                !velocity.is_finite() || velocity.length() > MAX_SPEED * TICK_DURATION
+            })
+            .map(|(key, _)| key)
+            .collect();
+
+        for key in unstable {
+            while let Some(&stick) = self.points[key].sticks.last() {
+                self.unlink_stick(stick);
             }
+            self.detach(key);
+            let point = &mut self.points[key];
+            let position = if point.last_position.is_finite() {
+                point.last_position
+            } else if point.position.is_finite() {
+                point.position
+            } else {
+                self.points.remove(key);
+                continue;
+            };
+            point.position = position;
+            point.last_position = position;
+            point.isolated = true;
         }
+    }
 
+    fn solve_parallel(&mut self) {
+        let colours = match self.colours.take() {
+            Some(colours) => colours,
+            None => colouring::colour(&self.points, &self.sticks),
+        };
+
+        let mut order: Vec<usize> = (0..colours.len()).collect();
+        let mut positions = Vec::new();
         for _ in 0..REPETITIONS {
-            let mut rng = rand::thread_rng();
-            let len = self.sticks.len();
-            let sticks = self.sticks.iter();
-            for Stick {
-                points: (key1, key2),
-                length,
-            } in sticks.choose_multiple(&mut rng, len)
-            {
-                let (point1, point2) = (&self.points[*key1], &self.points[*key2]);
-                let centre = (point1.position + point2.position) / 2.;
-                let offset = (point1.position - point2.position).normalize() * *length / 2.;
-                let (locked1, locked2) = (point1.locked, point2.locked);
-                if !locked1 {
-                    self.points[*key1].position = centre + offset;
+            order.shuffle(&mut self.rng);
+            for &colour in order.iter() {
+                let sticks = &colours[colour];
+                sticks
+                    .par_iter()
+                    .map(|&key| self.sticks[key].solve(&self.points))
+                    .collect_into_vec(&mut positions);
+                for (&key, &positions) in sticks.iter().zip(positions.iter()) {
+                    self.apply(key, positions);
+                }
+            }
+            self.solve_angles();
+            self.solve_joints();
+            self.solve_bodies();
+        }
+
+        self.colours = Some(colours);
+    }
+
+    fn apply(&mut self, key: usize, (position1, position2): (DVec2, DVec2)) {
+        let (key1, key2) = self.sticks[key].points;
+        if !self.points[key1].locked {
+            self.points[key1].position = position1;
+        }
+        if !self.points[key2].locked {
+            self.points[key2].position = position2;
+        }
+    }
+
+    fn solve_angles(&mut self) {
+        for (_, angle) in self.angles.iter() {
+            let (position1, position2) = angle.solve(&self.points);
+            let (key1, _, key2) = angle.points;
+            for (key, position) in [(key1, position1), (key2, position2)] {
+                let point = &mut self.points[key];
+                if !point.locked {
+                    point.position = position;
+                }
+            }
+        }
+    }
+
+    fn solve_joints(&mut self) {
+        for (_, pulley) in self.pulleys.iter() {
+            let (position1, position2) = pulley.solve(&self.points);
+            let (key1, _, key2) = pulley.points;
+            for (key, position) in [(key1, position1), (key2, position2)] {
+                let point = &mut self.points[key];
+                if !point.locked {
+                    point.position = position;
                 }
-                if !locked2 {
-                    self.points[*key2].position = centre - offset;
+            }
+        }
+        for (_, slider) in self.sliders.iter() {
+            let position = slider.solve(&self.points);
+            let point = &mut self.points[slider.point];
+            if !point.locked {
+                point.position = position;
+            }
+        }
+        for (_, pin) in self.pins.iter() {
+            let position = pin.solve(&self.points);
+            for key in [pin.points.0, pin.points.1] {
+                let point = &mut self.points[key];
+                if !point.locked {
+                    point.position = position;
+                }
+            }
+        }
+    }
+
+    fn solve_bodies(&mut self) {
+        for (_, body) in self.bodies.iter() {
+            for (&key, position) in body.points.iter().zip(body.solve(&self.points)) {
+                let point = &mut self.points[key];
+                if !point.locked {
+                    point.position = position;
                 }
             }
         }
     }
 
-    pub fn add_point(&mut self, position: DVec2) {
-        self.points.insert(Point::new(position));
+    pub fn add_point(&mut self, position: DVec2) -> usize {
+        let point = Point::new(position);
+        let (min, max) = point.bounds();
+        let key = self.points.insert(point);
+        self.point_grid.insert(key, min, max);
+        key
     }
 
-    pub fn add_stick(&mut self, key1: usize, key2: usize) {
+    pub fn add_stick(&mut self, key1: usize, key2: usize, kind: StickKind) -> Option<usize> {
         if let (Some(point1), Some(point2)) = (self.points.get(key1), self.points.get(key2)) {
-            self.sticks.insert(Stick::new(key1, key2, point1, point2));
+            if key1 == key2 || self.neighbours(key1).any(|key| key == key2) {
+                return None;
+            }
+            let key = self
+                .sticks
+                .insert(Stick::new(key1, key2, kind, point1, point2));
+            self.colours = None;
+            self.points[key1].sticks.push(key);
+            self.points[key2].sticks.push(key);
+            let (min, max) = self.stick_bounds(key);
+            self.stick_grid.insert(key, min, max);
+            Some(key)
+        } else {
+            None
+        }
+    }
+
+    // runs a rope from the first point over the second to the third, at its current length
+    pub fn add_pulley(&mut self, end1: usize, wheel: usize, end2: usize) {
+        if self.distinct(end1, wheel, end2) {
+            let pulley = Pulley::new((end1, wheel, end2), &self.points);
+            self.pulleys.insert(pulley);
+        }
+    }
+
+    // keeps the first point on the rail between the other two
+    pub fn add_slider(&mut self, key: usize, rail1: usize, rail2: usize) {
+        if self.distinct(key, rail1, rail2) {
+            self.sliders.insert(Slider::new(key, (rail1, rail2)));
+        }
+    }
+
+    // keeps two points together, letting them turn freely about each other
+    pub fn add_pin(&mut self, key1: usize, key2: usize) {
+        if key1 != key2 && self.points.contains(key1) && self.points.contains(key2) {
+            self.pins.insert(Pin::new((key1, key2)));
         }
     }
 
+    // merges the second point into the first, moving its sticks across at their new lengths and
+    // dropping any bending, joints or body it was part of
+    pub fn weld(&mut self, keep: usize, merge: usize) {
+        if keep == merge || !self.points.contains(keep) || !self.points.contains(merge) {
+            return;
+        }
+        let angles: Vec<usize> = self
+            .angles
+            .iter()
+            .filter(|(_, angle)| {
+                let (end1, pivot, end2) = angle.points;
+                merge == end1 || merge == pivot || merge == end2
+            })
+            .map(|(key, _)| key)
+            .collect();
+        for angle in angles {
+            self.remove_angle(angle);
+        }
+        self.detach(merge);
+
+        while let Some(&stick) = self.points[merge].sticks.last() {
+            let Stick {
+                points: (key1, key2),
+                kind,
+                motor,
+                style,
+                ..
+            } = self.sticks[stick];
+            let other = if key1 == merge { key2 } else { key1 };
+            self.remove_stick(stick);
+            if let Some(stick) = self.add_stick(keep, other, kind) {
+                self.set_motor(stick, motor);
+                self.set_style(stick, style);
+            }
+        }
+
+        self.points[keep].locked |= self.points[merge].locked;
+        self.colours = None;
+        let (min, max) = self.points[merge].bounds();
+        self.point_grid.remove(merge, min, max);
+        self.points.remove(merge);
+    }
+
+    fn distinct(&self, key1: usize, key2: usize, key3: usize) -> bool {
+        [key1, key2, key3]
+            .iter()
+            .all(|&key| self.points.contains(key))
+            && key1 != key2
+            && key2 != key3
+            && key1 != key3
+    }
+
+    // turns everything joined to a point by rods into one rigid body, or breaks up the body the
+    // point is already in
+    pub fn toggle_body(&mut self, key: usize) {
+        let point = match self.points.get(key) {
+            Some(point) => point,
+            None => return,
+        };
+        if let Some(body) = point.body {
+            self.remove_body(body);
+            return;
+        }
+
+        let mut points = vec![key];
+        let mut i = 0;
+        while let Some(&key) = points.get(i) {
+            for &stick in self.points[key].sticks.iter() {
+                let stick = &self.sticks[stick];
+                let other = if stick.points.0 == key {
+                    stick.points.1
+                } else {
+                    stick.points.0
+                };
+                if stick.kind == StickKind::Rod
+                    && self.points[other].body.is_none()
+                    && !points.contains(&other)
+                {
+                    points.push(other);
+                }
+            }
+            i += 1;
+        }
+        if points.len() < 2 {
+            return;
+        }
+
+        let body = self.bodies.vacant_key();
+        for &point in points.iter() {
+            self.points[point].body = Some(body);
+        }
+        self.bodies.insert(Body::new(points, &self.points));
+    }
+
+    // fills every triangle and quad of sticks in whatever the point is part of, mapping their
+    // texture across it as it is now, or empties them again if the point is already filled
+    pub fn toggle_faces(&mut self, key: usize) {
+        if !self.points.contains(key) {
+            return;
+        }
+        let mut adjacent: HashMap<usize, HashSet<usize>> = HashMap::new();
+        let mut queue = vec![key];
+        while let Some(key) = queue.pop() {
+            if let Entry::Vacant(entry) = adjacent.entry(key) {
+                let around: HashSet<usize> = self.neighbours(key).collect();
+                queue.extend(around.iter().copied());
+                entry.insert(around);
+            }
+        }
+
+        let filled = self.faces.iter().any(|(_, face)| face.contains(key));
+        self.faces
+            .retain(|_, face| !face.points.iter().any(|point| adjacent.contains_key(point)));
+        if filled {
+            return;
+        }
+
+        let (min, max) = adjacent.keys().fold(
+            (DVec2::splat(f64::INFINITY), DVec2::splat(f64::NEG_INFINITY)),
+            |(min, max), &key| {
+                let position = self.points[key].position;
+                (min.min(position), max.max(position))
+            },
+        );
+        let size = (max - min).max(DVec2::ONE);
+        for points in face::find(&adjacent) {
+            let uvs = points
+                .iter()
+                .map(|&key| (self.points[key].position - min) / size)
+                .collect();
+            self.faces.insert(Face::new(points, uvs));
+        }
+    }
+
+    fn remove_body(&mut self, key: usize) {
+        for point in self.bodies.remove(key).points {
+            self.points[point].body = None;
+        }
+    }
+
+    fn neighbours(&self, key: usize) -> impl Iterator<Item = usize> + '_ {
+        self.points[key].sticks.iter().map(move |&stick| {
+            let (key1, key2) = self.sticks[stick].points;
+            if key1 == key {
+                key2
+            } else {
+                key1
+            }
+        })
+    }
+
     pub fn get_point(&self, position: DVec2, distance: f64) -> Option<usize> {
-        let distance_squared = distance * distance;
-        for (key, point) in self.points.iter() {
-            if (position - point.position).length_squared() < distance_squared {
-                return Some(key);
+        let offset = DVec2::splat(distance);
+        let mut nearest = None;
+        let mut nearest_distance = distance * distance;
+        for key in self.point_grid.query(position - offset, position + offset) {
+            let distance_squared = (position - self.points[key].position).length_squared();
+            if distance_squared < nearest_distance {
+                nearest = Some(key);
+                nearest_distance = distance_squared;
+            }
+        }
+        nearest
+    }
+
+    pub fn get_stick(&self, position: DVec2, distance: f64) -> Option<usize> {
+        let offset = DVec2::splat(distance);
+        self.stick_grid
+            .query(position - offset, position + offset)
+            .into_iter()
+            .find(|&key| {
+                let (key1, key2) = self.sticks[key].points;
+                let (a, b) = (self.points[key1].position, self.points[key2].position);
+                // a still mouse against a point sweeping along the stick is a distance check
+                intersects_point(position, position, a, b, distance)
+            })
+    }
+
+    pub fn motor(&self, key: usize) -> Option<Motor> {
+        self.sticks.get(key)?.motor
+    }
+
+    pub fn set_motor(&mut self, key: usize, motor: Option<Motor>) {
+        if let Some(stick) = self.sticks.get_mut(key) {
+            if let Some(Motor::Oscillate { length, .. }) = stick.motor {
+                stick.length = length;
             }
+            stick.motor = motor;
+        }
+    }
+
+    pub fn style(&self, key: usize) -> Option<Style> {
+        Some(self.sticks.get(key)?.style)
+    }
+
+    pub fn set_style(&mut self, key: usize, style: Style) {
+        if let Some(stick) = self.sticks.get_mut(key) {
+            stick.style = style;
+        }
+    }
+
+    pub fn length(&self, key: usize) -> Option<f64> {
+        Some(self.sticks.get(key)?.length)
+    }
+
+    pub fn bending(&self, key: usize) -> Option<f64> {
+        let angle = *self.points.get(key)?.angles.first()?;
+        Some(self.angles[angle].stiffness)
+    }
+
+    // replaces the angle constraints at a point with ones between each of its sticks and the
+    // next one around it, keeping them at their current angles
+    pub fn set_bending(&mut self, key: usize, stiffness: Option<f64>) {
+        if !self.points.contains(key) {
+            return;
+        }
+        while let Some(&angle) = self.points[key].angles.last() {
+            self.remove_angle(angle);
+        }
+        let stiffness = match stiffness {
+            Some(stiffness) => stiffness,
+            None => return,
+        };
+
+        let pivot = self.points[key].position;
+        let mut sticks: Vec<(f64, usize, usize)> = self.points[key]
+            .sticks
+            .iter()
+            .zip(self.neighbours(key))
+            .map(|(&stick, end)| {
+                let arm = self.points[end].position - pivot;
+                (arm.y.atan2(arm.x), stick, end)
+            })
+            .collect();
+        sticks.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let pairs = match sticks.len() {
+            0 | 1 => 0,
+            2 => 1,
+            len => len,
+        };
+        for i in 0..pairs {
+            let (_, stick1, end1) = sticks[i];
+            let (_, stick2, end2) = sticks[(i + 1) % sticks.len()];
+            let angle = Angle::new((end1, key, end2), (stick1, stick2), stiffness, &self.points);
+            let angle = self.angles.insert(angle);
+            self.points[key].angles.push(angle);
+        }
+    }
+
+    fn remove_angle(&mut self, key: usize) {
+        let pivot = self.angles.remove(key).points.1;
+        self.points[pivot].angles.retain(|&angle| angle != key);
+    }
+
+    pub fn add_field(&mut self, field: Field) -> usize {
+        self.fields.insert(field)
+    }
+
+    pub fn remove_field(&mut self, key: usize) {
+        self.fields.try_remove(key);
+    }
+
+    pub fn get_fields(&self) -> impl Iterator<Item = (usize, &Field)> + '_ {
+        self.fields.iter()
+    }
+
+    // fills a polygon with water, if it has enough corners to have an inside
+    pub fn add_water(&mut self, polygon: Vec<DVec2>) {
+        if polygon.len() >= 3 {
+            self.waters.insert(Water::new(polygon));
         }
-        None
+    }
+
+    // removes the water the position is in, returning whether there was any
+    pub fn remove_water(&mut self, position: DVec2) -> bool {
+        let water = self
+            .waters
+            .iter()
+            .find(|(_, water)| water.contains(position))
+            .map(|(key, _)| key);
+        if let Some(key) = water {
+            self.waters.remove(key);
+        }
+        water.is_some()
+    }
+
+    pub fn get_waters(&self) -> impl Iterator<Item = &[DVec2]> + '_ {
+        self.waters.iter().map(|(_, water)| &water.polygon[..])
+    }
+
+    pub fn drag(&self) -> f64 {
+        self.drag
+    }
+
+    pub fn set_drag(&mut self, drag: f64) {
+        self.drag = drag;
     }
 
     pub fn toggle_locked(&mut self, key: usize) {
         if let Some(point) = self.points.get_mut(key) {
             point.locked = !point.locked;
+            self.colours = None;
         }
     }
 
     pub fn remove_points(&mut self, last: DVec2, current: DVec2, radius: f64) {
-        self.points.retain(
-            |key,
-             Point {
-                 position,
-                 last_position,
-                 ..
-             }| {
-                if intersects_point(last, current, *last_position, *position, radius) {
-                    self.sticks
-                        .retain(|stick| key != stick.points.0 && key != stick.points.1);
-                    return false;
+        let offset = DVec2::splat(radius);
+        let (min, max) = (last.min(current) - offset, last.max(current) + offset);
+        for key in self.point_grid.query(min, max) {
+            let Point {
+                position,
+                last_position,
+                ..
+            } = self.points[key];
+            if intersects_point(last, current, last_position, position, radius) {
+                while let Some(&stick) = self.points[key].sticks.last() {
+                    self.remove_stick(stick);
                 }
-                true
-            },
-        )
+                self.detach(key);
+                let (min, max) = self.points[key].bounds();
+                self.point_grid.remove(key, min, max);
+                self.points.remove(key);
+            }
+        }
     }
 
     pub fn remove_sticks(&mut self, last: DVec2, current: DVec2) {
-        self.sticks.retain(
-            |Stick {
-                 points: (key1, key2),
-                 ..
-             }| {
-                let (point1, point2) = (&self.points[*key1], &self.points[*key2]);
-                let (a0, a1) = (point1.last_position, point1.position);
-                let (b0, b1) = (point2.last_position, point2.position);
-                !intersects_stick(last, current, a0, a1, b0, b1)
-            },
-        );
+        for key in self.stick_grid.query(last.min(current), last.max(current)) {
+            let (key1, key2) = self.sticks[key].points;
+            let (point1, point2) = (&self.points[key1], &self.points[key2]);
+            let (a0, a1) = (point1.last_position, point1.position);
+            let (b0, b1) = (point2.last_position, point2.position);
+            if intersects_stick(last, current, a0, a1, b0, b1) {
+                self.remove_stick(key);
+            }
+        }
+    }
+
+    fn remove_stick(&mut self, key: usize) {
+        let (min, max) = self.stick_bounds(key);
+        self.stick_grid.remove(key, min, max);
+        self.unlink_stick(key);
+    }
+
+    // removes a stick without updating the grid, for when it is about to be rebuilt
+    fn unlink_stick(&mut self, key: usize) {
+        let (key1, key2) = self.sticks.remove(key).points;
+        self.colours = None;
+        for pivot in [key1, key2] {
+            let angles: Vec<usize> = self.points[pivot]
+                .angles
+                .iter()
+                .copied()
+                .filter(|&angle| {
+                    let sticks = self.angles[angle].sticks;
+                    sticks.0 == key || sticks.1 == key
+                })
+                .collect();
+            for angle in angles {
+                self.remove_angle(angle);
+            }
+        }
+        self.points[key1].sticks.retain(|&stick| stick != key);
+        self.points[key2].sticks.retain(|&stick| stick != key);
+    }
+
+    // removes a point from everything holding it other than its sticks
+    fn detach(&mut self, key: usize) {
+        self.pulleys.retain(|_, pulley| !pulley.contains(key));
+        self.sliders.retain(|_, slider| !slider.contains(key));
+        self.pins.retain(|_, pin| !pin.contains(key));
+        self.faces.retain(|_, face| !face.contains(key));
+        if let Some(body) = self.points[key].body.take() {
+            self.bodies[body].remove(key);
+            if self.bodies[body].points.len() < 2 {
+                self.remove_body(body);
+            }
+        }
     }
 
-    pub fn get_points(&self, t: f64) -> impl Iterator<Item = (DVec2, bool)> + '_ {
+    fn stick_bounds(&self, key: usize) -> (DVec2, DVec2) {
+        let (key1, key2) = self.sticks[key].points;
+        let (min1, max1) = self.points[key1].bounds();
+        let (min2, max2) = self.points[key2].bounds();
+        (min1.min(min2), max1.max(max2))
+    }
+
+    fn index(&mut self) {
+        self.point_grid.clear();
+        for (key, point) in self.points.iter() {
+            let (min, max) = point.bounds();
+            self.point_grid.insert(key, min, max);
+        }
+
+        self.stick_grid.clear();
+        for (key, _) in self.sticks.iter() {
+            let (min, max) = self.stick_bounds(key);
+            self.stick_grid.insert(key, min, max);
+        }
+    }
+
+    pub fn get_points(&self, t: f64) -> impl Iterator<Item = (usize, DVec2, bool)> + '_ {
         self.points
             .iter()
-            .map(move |(_, point)| (point.interpolate(t), point.locked))
+            .map(move |(key, point)| (key, point.interpolate(t), point.locked))
+    }
+
+    // in pixels per second
+    pub fn get_velocities(&self, t: f64) -> impl Iterator<Item = (DVec2, DVec2)> + '_ {
+        self.points.iter().map(move |(_, point)| {
+            let velocity = (point.position - point.last_position) / TICK_DURATION;
+            (point.interpolate(t), velocity)
+        })
     }
 
-    pub fn get_sticks(&self, t: f64) -> impl Iterator<Item = (DVec2, DVec2)> + '_ {
+    pub fn get_sticks(
+        &self,
+        t: f64,
+    ) -> impl Iterator<Item = (DVec2, DVec2, StickKind, Style, bool, f64)> + '_ {
         self.sticks.iter().map(
-            move |Stick {
-                      points: (key1, key2),
-                      ..
-                  }| {
+            move |(
+                _,
+                Stick {
+                    points: (key1, key2),
+                    kind,
+                    style,
+                    motor,
+                    tension,
+                    ..
+                },
+            )| {
                 (
                     self.points[*key1].interpolate(t),
                     self.points[*key2].interpolate(t),
+                    *kind,
+                    *style,
+                    motor.is_some(),
+                    *tension,
                 )
             },
         )
     }
 
+    pub fn get_angles(&self, t: f64) -> impl Iterator<Item = (DVec2, DVec2, DVec2, f64)> + '_ {
+        self.angles.iter().map(move |(_, angle)| {
+            let (key1, pivot, key2) = angle.points;
+            (
+                self.points[pivot].interpolate(t),
+                self.points[key1].interpolate(t),
+                self.points[key2].interpolate(t),
+                angle.stiffness,
+            )
+        })
+    }
+
+    pub fn get_pulleys(&self, t: f64) -> impl Iterator<Item = (DVec2, DVec2, DVec2)> + '_ {
+        self.pulleys.iter().map(move |(_, pulley)| {
+            let (key1, wheel, key2) = pulley.points;
+            (
+                self.points[wheel].interpolate(t),
+                self.points[key1].interpolate(t),
+                self.points[key2].interpolate(t),
+            )
+        })
+    }
+
+    pub fn get_sliders(&self, t: f64) -> impl Iterator<Item = (DVec2, DVec2)> + '_ {
+        self.sliders.iter().map(move |(_, slider)| {
+            let (key1, key2) = slider.rail;
+            (
+                self.points[key1].interpolate(t),
+                self.points[key2].interpolate(t),
+            )
+        })
+    }
+
+    pub fn get_pins(&self, t: f64) -> impl Iterator<Item = DVec2> + '_ {
+        self.pins
+            .iter()
+            .map(move |(_, pin)| self.points[pin.points.0].interpolate(t))
+    }
+
+    pub fn get_bodies(&self, t: f64) -> impl Iterator<Item = Vec<DVec2>> + '_ {
+        self.bodies.iter().map(move |(_, body)| {
+            body.points
+                .iter()
+                .map(|&key| self.points[key].interpolate(t))
+                .collect()
+        })
+    }
+
+    // runs of sticks of the same kind and style joined end to end through points with no other
+    // sticks, with whether each loops back round to where it starts
+    pub fn get_chains(&self, t: f64) -> Vec<(Vec<DVec2>, bool, StickKind, Style)> {
+        let mut visited = vec![false; self.sticks.capacity()];
+        let mut chains = Vec::new();
+        for (key, stick) in self.sticks.iter() {
+            if visited[key] {
+                continue;
+            }
+            visited[key] = true;
+            let mut points = VecDeque::from([stick.points.0, stick.points.1]);
+            for forward in [true, false] {
+                let mut last = key;
+                loop {
+                    let end = if forward {
+                        points.back()
+                    } else {
+                        points.front()
+                    };
+                    let end = *end.unwrap();
+                    let sticks = &self.points[end].sticks;
+                    if sticks.len() != 2 {
+                        break;
+                    }
+                    let next = if sticks[0] == last {
+                        sticks[1]
+                    } else {
+                        sticks[0]
+                    };
+                    let other = &self.sticks[next];
+                    if visited[next] || other.kind != stick.kind || other.style != stick.style {
+                        break;
+                    }
+                    visited[next] = true;
+                    let point = if other.points.0 == end {
+                        other.points.1
+                    } else {
+                        other.points.0
+                    };
+                    if forward {
+                        points.push_back(point);
+                    } else {
+                        points.push_front(point);
+                    }
+                    last = next;
+                }
+            }
+            let closed = points.len() > 3 && points.front() == points.back();
+            if closed {
+                points.pop_back();
+            }
+            let positions = points
+                .iter()
+                .map(|&key| self.points[key].interpolate(t))
+                .collect();
+            chains.push((positions, closed, stick.kind, stick.style));
+        }
+        chains
+    }
+
+    // the corners of each face with their texture coordinates
+    pub fn get_faces(&self, t: f64) -> impl Iterator<Item = Vec<(DVec2, DVec2)>> + '_ {
+        self.faces.iter().map(move |(_, face)| {
+            face.points
+                .iter()
+                .zip(face.uvs.iter())
+                .map(|(&key, &uv)| (self.points[key].interpolate(t), uv))
+                .collect()
+        })
+    }
+
+    pub fn get_isolated(&self) -> impl Iterator<Item = DVec2> + '_ {
+        self.points
+            .iter()
+            .filter(|(_, point)| point.isolated)
+            .map(|(_, point)| point.position)
+    }
+
+    pub fn contains(&self, key: usize) -> bool {
+        self.points.contains(key)
+    }
+
+    // the average position of the points, which all weigh the same
+    pub fn centre(&self, t: f64) -> Option<DVec2> {
+        if self.points.is_empty() {
+            return None;
+        }
+        let total = self.points.iter().fold(DVec2::ZERO, |total, (_, point)| {
+            total + point.interpolate(t)
+        });
+        Some(total / self.points.len() as f64)
+    }
+
     pub fn get_position(&self, key: usize, t: f64) -> DVec2 {
         self.points[key].interpolate(t)
     }
+
+    pub fn statistics(&self) -> Statistics {
+        let mut statistics = Statistics {
+            points: self.points.len(),
+            locked: 0,
+            sticks: self.sticks.len(),
+            isolated: Vec::new(),
+            centre_of_mass: DVec2::ZERO,
+            min: DVec2::ZERO,
+            max: DVec2::ZERO,
+            kinetic_energy: 0.,
+            potential_energy: 0.,
+            max_strain: 0.,
+            rms_error: 0.,
+        };
+
+        if let Some((_, point)) = self.points.iter().next() {
+            statistics.min = point.position;
+            statistics.max = point.position;
+        }
+        for (key, point) in self.points.iter() {
+            if point.isolated {
+                statistics.isolated.push(key);
+            } else if point.locked {
+                statistics.locked += 1;
+            } else {
+                let velocity = (point.position - point.last_position) / TICK_DURATION;
+                statistics.kinetic_energy += velocity.length_squared() / 2.;
+                // y points down, so points lose potential energy as it grows
+                statistics.potential_energy -= GRAVITY * point.position.y;
+            }
+            statistics.centre_of_mass += point.position;
+            statistics.min = statistics.min.min(point.position);
+            statistics.max = statistics.max.max(point.position);
+        }
+        if !self.points.is_empty() {
+            statistics.centre_of_mass /= self.points.len() as f64;
+        }
+
+        for (_, stick) in self.sticks.iter() {
+            let strain = stick.strain(&self.points);
+            statistics.max_strain = statistics.max_strain.max(strain.abs());
+            statistics.rms_error += stick.error(&self.points).powi(2);
+        }
+        if !self.sticks.is_empty() {
+            statistics.rms_error = (statistics.rms_error / self.sticks.len() as f64).sqrt();
+        }
+
+        statistics
+    }
+}
+
+impl Default for Ropes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Serialize)]
+pub struct Statistics {
+    pub points: usize,
+    pub locked: usize,
+    pub sticks: usize,
+    pub isolated: Vec<usize>,
+    pub centre_of_mass: DVec2,
+    pub min: DVec2,
+    pub max: DVec2,
+    pub kinetic_energy: f64,
+    // relative to where y is 0
+    pub potential_energy: f64,
+    pub max_strain: f64,
+    // of the difference between each stick's length and the distance between its ends, in pixels
+    pub rms_error: f64,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Point {
     position: DVec2,
     last_position: DVec2,
     locked: bool,
+    #[serde(default)]
+    isolated: bool,
+    #[serde(skip)]
+    sticks: Vec<usize>,
+    #[serde(skip)]
+    angles: Vec<usize>,
+    #[serde(skip)]
+    body: Option<usize>,
 }
 
 impl Point {
@@ -155,55 +1137,112 @@ impl Point {
             position,
             last_position: position,
             locked: false,
+            isolated: false,
+            sticks: Vec::new(),
+            angles: Vec::new(),
+            body: None,
         }
     }
 
     fn interpolate(&self, t: f64) -> DVec2 {
         self.last_position.lerp(self.position, t)
     }
+
+    fn bounds(&self) -> (DVec2, DVec2) {
+        (
+            self.position.min(self.last_position),
+            self.position.max(self.last_position),
+        )
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Stick {
     points: (usize, usize),
     length: f64,
+    #[serde(default)]
+    kind: StickKind,
+    #[serde(default)]
+    motor: Option<Motor>,
+    #[serde(default)]
+    style: Style,
+    // the strain the solver could not take out of the stick in the last tick, which is highest
+    // where the most is hanging off it
+    #[serde(skip)]
+    tension: f64,
 }
 
 impl Stick {
-    fn new(key1: usize, key2: usize, point1: &Point, point2: &Point) -> Self {
+    fn new(key1: usize, key2: usize, kind: StickKind, point1: &Point, point2: &Point) -> Self {
         Self {
             points: (key1, key2),
             length: (point1.position - point2.position).length(),
+            kind,
+            motor: None,
+            style: Style::default(),
+            tension: 0.,
         }
     }
-}
 
-impl PartialEq for Stick {
-    fn eq(&self, other: &Self) -> bool {
-        self.points == other.points || (self.points.1, self.points.0) == other.points
+    // how much longer the stick is than its length, in pixels, ignoring slack in ropes
+    fn error(&self, points: &Slab<Point>) -> f64 {
+        let current = (points[self.points.0].position - points[self.points.1].position).length();
+        let error = current - self.length;
+        match self.kind {
+            StickKind::Rod => error,
+            StickKind::Rope => error.max(0.),
+        }
     }
-}
 
-impl Eq for Stick {}
+    // how far the stick is stretched beyond its length, as a fraction of it; sticks with no
+    // length have no strain to measure, so they count as unstrained
+    fn strain(&self, points: &Slab<Point>) -> f64 {
+        if self.length == 0. {
+            return 0.;
+        }
+        self.error(points) / self.length
+    }
 
-impl Hash for Stick {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        if self.points.0 <= self.points.1 {
-            self.points.hash(state);
-        } else {
-            (self.points.1, self.points.0).hash(state);
+    fn solve(&self, points: &Slab<Point>) -> (DVec2, DVec2) {
+        let (point1, point2) = (&points[self.points.0], &points[self.points.1]);
+        if self.kind == StickKind::Rope
+            && (point1.position - point2.position).length_squared() <= self.length * self.length
+        {
+            return (point1.position, point2.position);
         }
+        let centre = (point1.position + point2.position) / 2.;
+        // coincident points have no direction between them, so keep the one from the last tick
+        let direction = (point1.position - point2.position)
+            .try_normalize()
+            .or_else(|| (point1.last_position - point2.last_position).try_normalize())
+            .unwrap_or(DVec2::Y);
+        let offset = direction * self.length / 2.;
+        (centre + offset, centre - offset)
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum StickKind {
+    /// Keeps its ends exactly its length apart.
+    #[default]
+    Rod,
+    /// Only stops its ends moving further apart than its length, going slack otherwise.
+    Rope,
+}
+
+/// How a stick is drawn, where it should stand out from the theme.
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Style {
+    pub colour: Option<(u8, u8, u8)>,
+    pub width: Option<f32>,
+}
+
 fn intersects_point(m0: DVec2, m1: DVec2, c0: DVec2, c1: DVec2, radius: f64) -> bool {
     let m0 = m0 - c0;
     let m1 = m1 - c1;
     let c = m0.dot(m0);
     let r = radius * radius;
-    if c < r {
-        true
-    } else if m1.dot(m1) < r {
+    if c < r || m1.dot(m1) < r {
         true
     } else {
         let diff = m1 - m0;
@@ -227,10 +1266,16 @@ fn intersects_stick(m0: DVec2, m1: DVec2, a0: DVec2, a1: DVec2, b0: DVec2, b1: D
     let a = (m1 - m0).perp_dot(b1 - b0);
     let b = m0.perp_dot(b1) + m1.perp_dot(b0) + 2. * b0.perp_dot(m0);
     let c = m0.perp_dot(b0);
-    if approx_eq(a, 0.) {
-        if approx_eq(b, 0.) {
-            if approx_eq(c, 0.) && valid(m0, m1, b0, b1, 0.5) {
-                return true;
+    let scale = [m0, m1, b0, b1]
+        .iter()
+        .map(|v| v.length_squared())
+        .fold(0., f64::max);
+    if approx_eq(a, 0., scale) {
+        if approx_eq(b, 0., scale) {
+            if approx_eq(c, 0., scale) {
+                // the mouse moves along the line through the stick
+                let (u0, u1) = (m0.dot(b0) / b0.dot(b0), m1.dot(b1) / b1.dot(b1));
+                return u0.min(u1) < 1. && u0.max(u1) > 0.;
             }
         } else if valid(m0, m1, b0, b1, -c / b) {
             return true;
@@ -238,11 +1283,12 @@ fn intersects_stick(m0: DVec2, m1: DVec2, a0: DVec2, a1: DVec2, b0: DVec2, b1: D
     } else {
         let det = b * b - 4. * a * c;
         if det >= 0. {
-            let det = det.sqrt();
-            if valid(m0, m1, b0, b1, (-b - det) / (2. * a)) {
+            // avoids cancellation between b and the root of det when a is small
+            let q = -(b + b.signum() * det.sqrt()) / 2.;
+            if valid(m0, m1, b0, b1, q / a) {
                 return true;
             }
-            if valid(m0, m1, b0, b1, (-b + det) / (2. * a)) {
+            if valid(m0, m1, b0, b1, c / q) {
                 return true;
             }
         }
@@ -260,6 +1306,890 @@ fn valid(m0: DVec2, m1: DVec2, b0: DVec2, b1: DVec2, t: f64) -> bool {
     false
 }
 
-fn approx_eq(a: f64, b: f64) -> bool {
-    (a - b).abs() < f64::EPSILON
+// compares values computed from positions whose squared size is around scale
+fn approx_eq(a: f64, b: f64, scale: f64) -> bool {
+    (a - b).abs() <= scale * TOLERANCE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::process;
+
+    const SAMPLES: u32 = 4096;
+
+    // closest approach by sampling the tick, or None if it is too close to the radius to call
+    fn sampled_point(m0: DVec2, m1: DVec2, c0: DVec2, c1: DVec2, radius: f64) -> Option<bool> {
+        let (q0, q1) = (m0 - c0, m1 - c1);
+        let closest = (0..=SAMPLES)
+            .map(|i| q0.lerp(q1, i as f64 / SAMPLES as f64).length())
+            .fold(f64::INFINITY, f64::min);
+        let margin = (q1 - q0).length() / SAMPLES as f64 + 1e-6;
+        if (closest - radius).abs() <= margin {
+            None
+        } else {
+            Some(closest < radius)
+        }
+    }
+
+    // whether the mouse crosses the inside of the stick by sampling the tick, or None if it passes
+    // too close to an end or only grazes the stick
+    fn sampled_stick(
+        m0: DVec2,
+        m1: DVec2,
+        a0: DVec2,
+        a1: DVec2,
+        b0: DVec2,
+        b1: DVec2,
+    ) -> Option<bool> {
+        let samples: Vec<(f64, f64, f64)> = (0..=SAMPLES)
+            .map(|i| {
+                let t = i as f64 / SAMPLES as f64;
+                let (m, a, b) = (m0.lerp(m1, t), a0.lerp(a1, t), b0.lerp(b1, t));
+                let (stick, mouse) = (b - a, m - a);
+                let length = stick.length();
+                (
+                    mouse.perp_dot(stick) / length,
+                    mouse.dot(stick) / (length * length),
+                    length,
+                )
+            })
+            .collect();
+        if samples.iter().all(|&(_, _, length)| length == 0.) {
+            return Some(false);
+        }
+        if samples.iter().any(|&(_, _, length)| length < 1e-3) {
+            return None;
+        }
+
+        let step = |f: fn(&(f64, f64, f64)) -> f64| {
+            samples
+                .windows(2)
+                .map(|w| (f(&w[1]) - f(&w[0])).abs())
+                .fold(0., f64::max)
+        };
+        let distance_margin = 4. * step(|s| s.0) + 1e-6;
+        let projection_margin = 4. * step(|s| s.1) + 1e-6;
+
+        let near = |&(distance, projection, _): &(f64, f64, f64)| {
+            distance.abs() < distance_margin
+                && projection > -projection_margin
+                && projection < 1. + projection_margin
+        };
+        if near(&samples[0]) || near(&samples[samples.len() - 1]) {
+            return None;
+        }
+
+        // compares each pair of samples far enough from the line to have a clear side
+        let mut crossed = false;
+        let mut last = 0;
+        for i in 1..samples.len() {
+            let (d0, p0, _) = samples[last];
+            let (d1, p1, _) = samples[i];
+            if d1.abs() < distance_margin {
+                continue;
+            }
+            if d0 * d1 < 0. {
+                let projection = p0 + (p1 - p0) * d0 / (d0 - d1);
+                let margin = projection_margin + (p1 - p0).abs();
+                if projection.abs() < margin || (projection - 1.).abs() < margin {
+                    return None;
+                }
+                crossed |= projection > 0. && projection < 1.;
+            } else if samples[last..i].iter().any(near) {
+                return None;
+            }
+            last = i;
+        }
+        if samples[last..].iter().any(near) {
+            return None;
+        }
+        Some(crossed)
+    }
+
+    fn vector() -> impl Strategy<Value = DVec2> {
+        (-500.0..500.0, -500.0..500.0).prop_map(|(x, y)| DVec2::new(x, y))
+    }
+
+    // how far a point moved in its last tick, sometimes far enough for the grid to hold it apart
+    fn motion() -> impl Strategy<Value = DVec2> {
+        prop_oneof![
+            Just(DVec2::ZERO),
+            vector().prop_map(|v| v * 0.1),
+            vector().prop_map(|v| v * 10.),
+        ]
+    }
+
+    // points with their motion in the last tick, joined by sticks between pairs of them
+    fn scene(points: &[(DVec2, DVec2)], sticks: &[(usize, usize)]) -> Ropes {
+        let mut ropes = Ropes::new();
+        let keys: Vec<usize> = points
+            .iter()
+            .map(|&(position, _)| ropes.add_point(position))
+            .collect();
+        for &(i, j) in sticks {
+            ropes.add_stick(keys[i % keys.len()], keys[j % keys.len()], StickKind::Rod);
+        }
+        for (&key, &(position, motion)) in keys.iter().zip(points) {
+            ropes.points[key].last_position = position - motion;
+        }
+        ropes.index();
+        ropes
+    }
+
+    type Scene = (Vec<(DVec2, DVec2)>, Vec<(usize, usize)>);
+
+    fn scene_strategy() -> impl Strategy<Value = Scene> {
+        (
+            prop::collection::vec((vector(), motion()), 2..24),
+            prop::collection::vec((0..24usize, 0..24usize), 0..32),
+        )
+    }
+
+    // whole pixel positions on a coarse grid, which produce exactly parallel and collinear motion
+    fn grid_vector() -> impl Strategy<Value = DVec2> {
+        (-8..8, -8..8).prop_map(|(x, y)| DVec2::new(x as f64, y as f64) * 50.)
+    }
+
+    #[test]
+    fn coincident_points_are_pushed_apart() {
+        let mut ropes = Ropes::new();
+        let key1 = ropes.add_point(DVec2::ZERO);
+        let key2 = ropes.add_point(DVec2::new(10., 0.));
+        ropes.add_stick(key1, key2, StickKind::Rod);
+        ropes.points[key2].position = DVec2::ZERO;
+        ropes.points[key2].last_position = DVec2::ZERO;
+        ropes.tick();
+
+        let (point1, point2) = (&ropes.points[key1], &ropes.points[key2]);
+        assert!(point1.position.is_finite() && point2.position.is_finite());
+        assert!(((point1.position - point2.position).length() - 10.).abs() < 1e-9);
+        assert!(ropes.statistics().isolated.is_empty());
+    }
+
+    #[test]
+    fn zero_length_sticks_have_no_strain() {
+        let mut ropes = Ropes::new();
+        let key1 = ropes.add_point(DVec2::ZERO);
+        let key2 = ropes.add_point(DVec2::ZERO);
+        let stick = ropes.add_stick(key1, key2, StickKind::Rod).unwrap();
+        ropes.toggle_locked(key1);
+        ropes.tick();
+
+        assert_eq!(ropes.sticks[stick].tension, 0.);
+        assert_eq!(ropes.statistics().max_strain, 0.);
+        assert!(ropes.statistics().rms_error.is_finite());
+    }
+
+    #[test]
+    fn exploding_points_are_isolated() {
+        let mut ropes = Ropes::new();
+        let key1 = ropes.add_point(DVec2::ZERO);
+        let key2 = ropes.add_point(DVec2::new(10., 0.));
+        let key3 = ropes.add_point(DVec2::new(20., 0.));
+        ropes.add_stick(key1, key2, StickKind::Rod);
+        ropes.add_stick(key2, key3, StickKind::Rod);
+        ropes.points[key3].last_position = DVec2::new(f64::NAN, 0.);
+        ropes.tick();
+
+        assert_eq!(ropes.statistics().isolated, vec![key3]);
+        assert_eq!(ropes.points[key3].position, DVec2::new(20., 0.));
+        assert_eq!(ropes.sticks.len(), 1);
+        assert!(ropes.points[key1].position.is_finite() && ropes.points[key2].position.is_finite());
+    }
+
+    #[test]
+    fn bending_holds_a_cantilever_up() {
+        let mut ropes = Ropes::new();
+        let keys = [0., 20., 40.].map(|x| ropes.add_point(DVec2::new(x, 0.)));
+        ropes.toggle_locked(keys[0]);
+        ropes.toggle_locked(keys[1]);
+        ropes.add_stick(keys[0], keys[1], StickKind::Rod);
+        ropes.add_stick(keys[1], keys[2], StickKind::Rod);
+        ropes.set_bending(keys[1], Some(0.5));
+        for _ in 0..100 {
+            ropes.tick();
+        }
+        assert!(ropes.points[keys[2]].position.y < 5.);
+
+        ropes.remove_sticks(DVec2::new(30., -10.), DVec2::new(30., 10.));
+        assert!(ropes.angles.is_empty());
+        assert!(ropes.bending(keys[1]).is_none());
+    }
+
+    #[test]
+    fn ropes_go_slack_but_do_not_stretch() {
+        let mut ropes = Ropes::new();
+        let top = ropes.add_point(DVec2::ZERO);
+        let bottom = ropes.add_point(DVec2::new(0., 100.));
+        ropes.toggle_locked(top);
+        ropes.add_stick(top, bottom, StickKind::Rope);
+        for _ in 0..100 {
+            ropes.tick();
+        }
+        let length = ropes.points[bottom].position.length();
+        assert!((length - 100.).abs() < 0.1);
+
+        ropes.points[bottom].position = DVec2::new(0., 50.);
+        ropes.points[bottom].last_position = DVec2::new(0., 50.);
+        ropes.points[bottom].locked = true;
+        ropes.tick();
+        assert_eq!(ropes.points[bottom].position, DVec2::new(0., 50.));
+        assert_eq!(ropes.statistics().max_strain, 0.);
+    }
+
+    #[test]
+    fn pulling_one_side_of_a_pulley_lifts_the_other() {
+        let mut ropes = Ropes::new();
+        let left = ropes.add_point(DVec2::new(-20., 100.));
+        let wheel = ropes.add_point(DVec2::ZERO);
+        let right = ropes.add_point(DVec2::new(20., 100.));
+        ropes.toggle_locked(wheel);
+        ropes.add_pulley(left, wheel, right);
+
+        ropes.points[left].position.y = 150.;
+        ropes.points[left].last_position.y = 150.;
+        ropes.toggle_locked(left);
+        for _ in 0..100 {
+            ropes.tick();
+        }
+        let length = (ropes.points[left].position - ropes.points[wheel].position).length()
+            + (ropes.points[right].position - ropes.points[wheel].position).length();
+        assert!((length - ropes.pulleys[0].length).abs() < 0.1);
+        assert!(ropes.points[right].position.y < 60.);
+
+        ropes.remove_points(DVec2::new(-50., -50.), DVec2::new(50., -50.), 100.);
+        assert!(ropes.pulleys.is_empty());
+    }
+
+    #[test]
+    fn bodies_keep_an_unbraced_square_square() {
+        let mut ropes = Ropes::new();
+        let corners = [(0., 0.), (50., 0.), (50., 50.), (0., 50.)]
+            .map(|(x, y)| ropes.add_point(DVec2::new(x, y)));
+        for i in 0..4 {
+            ropes.add_stick(corners[i], corners[(i + 1) % 4], StickKind::Rod);
+        }
+        let hanging = ropes.add_point(DVec2::new(100., 0.));
+        ropes.add_stick(corners[1], hanging, StickKind::Rope);
+        ropes.toggle_locked(corners[0]);
+        ropes.toggle_body(corners[2]);
+        assert_eq!(ropes.bodies[0].points.len(), 4);
+
+        for _ in 0..100 {
+            ropes.tick();
+        }
+        let position = |key: usize| ropes.points[corners[key]].position;
+        assert!((position(0).distance(position(2)) - 50. * 2f64.sqrt()).abs() < 0.1);
+        assert!((position(1).distance(position(3)) - 50. * 2f64.sqrt()).abs() < 0.1);
+
+        ropes.remove_points(DVec2::ZERO, DVec2::ZERO, 1.);
+        assert_eq!(ropes.bodies[0].points.len(), 3);
+        ropes.toggle_body(corners[1]);
+        assert!(ropes.bodies.is_empty());
+        assert!(ropes.points.iter().all(|(_, point)| point.body.is_none()));
+    }
+
+    #[test]
+    fn wind_blows_a_hanging_rope_through_its_drag() {
+        let hanging = |drag: f64| {
+            let mut ropes = Ropes::new();
+            let keys = [0., 20., 40.].map(|y| ropes.add_point(DVec2::new(0., y)));
+            ropes.toggle_locked(keys[0]);
+            ropes.add_stick(keys[0], keys[1], StickKind::Rod);
+            ropes.add_stick(keys[1], keys[2], StickKind::Rod);
+            ropes.add_field(Field::Wind {
+                velocity: DVec2::new(300., 0.),
+                turbulence: 0.,
+            });
+            ropes.set_drag(drag);
+            for _ in 0..200 {
+                ropes.tick();
+            }
+            ropes.points[keys[2]].position
+        };
+        assert!(hanging(0.).x.abs() < 1e-9);
+        assert!(hanging(0.2).x > 10.);
+    }
+
+    #[test]
+    fn attractors_pull_and_vortices_spin() {
+        let mut ropes = Ropes::new();
+        let key = ropes.add_point(DVec2::new(100., 0.));
+        ropes.add_field(Field::Radial {
+            centre: DVec2::ZERO,
+            radius: 200.,
+            strength: 1000.,
+        });
+        ropes.tick();
+        assert!(ropes.points[key].position.x < 100.);
+
+        let mut ropes = Ropes::new();
+        let key = ropes.add_point(DVec2::new(100., 0.));
+        ropes.add_field(Field::Vortex {
+            centre: DVec2::ZERO,
+            radius: 200.,
+            strength: 5000.,
+        });
+        ropes.tick();
+        let moved = ropes.points[key].position - DVec2::new(100., TICK_GRAVITY);
+        assert!(moved.y > 0. && moved.x.abs() < 1e-9);
+    }
+
+    #[test]
+    fn points_float_up_to_the_surface_of_water() {
+        let mut ropes = Ropes::new();
+        let floating = ropes.add_point(DVec2::new(50., 150.));
+        let falling = ropes.add_point(DVec2::new(150., 150.));
+        ropes.add_water(vec![
+            DVec2::new(0., 100.),
+            DVec2::new(100., 100.),
+            DVec2::new(100., 200.),
+            DVec2::new(0., 200.),
+        ]);
+        ropes.add_water(vec![DVec2::ZERO, DVec2::X]);
+        assert_eq!(ropes.waters.len(), 1);
+
+        for _ in 0..200 {
+            ropes.tick();
+        }
+        let y = ropes.points[floating].position.y;
+        assert!((90. ..110.).contains(&y));
+        assert!(ropes.points[falling].position.y > 1000.);
+
+        assert!(!ropes.remove_water(DVec2::new(150., 150.)));
+        assert!(ropes.remove_water(DVec2::new(50., 150.)));
+        assert!(ropes.waters.is_empty());
+    }
+
+    #[test]
+    fn winches_reel_ropes_in_and_muscles_oscillate() {
+        let mut ropes = Ropes::new();
+        let top = ropes.add_point(DVec2::ZERO);
+        let bottom = ropes.add_point(DVec2::new(0., 100.));
+        ropes.toggle_locked(top);
+        ropes.add_stick(top, bottom, StickKind::Rope);
+        let stick = ropes.get_stick(DVec2::new(3., 50.), 5.).unwrap();
+        assert!(ropes.get_stick(DVec2::new(10., 50.), 5.).is_none());
+        ropes.set_motor(stick, Some(Motor::Winch { speed: 64. }));
+
+        ropes.set_winding(-1.);
+        for _ in 0..32 {
+            ropes.tick();
+        }
+        assert!((ropes.sticks[stick].length - 36.).abs() < 1e-9);
+        ropes.set_winding(0.);
+        ropes.tick();
+        assert!((ropes.sticks[stick].length - 36.).abs() < 1e-9);
+
+        ropes.set_motor(
+            stick,
+            Some(Motor::Oscillate {
+                length: 50.,
+                amplitude: 10.,
+                period: 1.,
+            }),
+        );
+        let lengths: Vec<f64> = (0..32)
+            .map(|_| {
+                ropes.tick();
+                ropes.sticks[stick].length
+            })
+            .collect();
+        assert!(lengths.iter().all(|length| (40. ..=60.).contains(length)));
+        assert!(lengths.iter().any(|&length| length > 59.));
+        assert!(lengths.iter().any(|&length| length < 41.));
+
+        ropes.set_motor(stick, None);
+        assert_eq!(ropes.sticks[stick].length, 50.);
+    }
+
+    #[test]
+    fn welding_moves_sticks_onto_the_kept_point() {
+        let mut ropes = Ropes::new();
+        let keys = [(0., 0.), (50., 0.), (52., 0.), (100., 0.), (50., 50.)]
+            .map(|(x, y)| ropes.add_point(DVec2::new(x, y)));
+        ropes.add_stick(keys[0], keys[1], StickKind::Rod);
+        ropes.add_stick(keys[1], keys[4], StickKind::Rod);
+        let rope = ropes.add_stick(keys[2], keys[3], StickKind::Rope).unwrap();
+        ropes.add_stick(keys[2], keys[4], StickKind::Rod);
+        ropes.add_stick(keys[1], keys[2], StickKind::Rod);
+        let style = Style {
+            colour: Some((255, 0, 0)),
+            width: Some(3.),
+        };
+        ropes.set_style(rope, style);
+        ropes.toggle_locked(keys[2]);
+        ropes.add_pin(keys[2], keys[3]);
+
+        ropes.weld(keys[1], keys[2]);
+        assert!(!ropes.points.contains(keys[2]));
+        assert!(ropes.points[keys[1]].locked);
+        assert!(ropes.pins.is_empty());
+        let mut neighbours: Vec<usize> = ropes.neighbours(keys[1]).collect();
+        neighbours.sort();
+        assert_eq!(neighbours, vec![keys[0], keys[3], keys[4]]);
+        assert_eq!(ropes.sticks.len(), 3);
+        assert!(ropes
+            .sticks
+            .iter()
+            .all(|(_, stick)| stick.strain(&ropes.points) == 0.));
+        assert!(ropes
+            .sticks
+            .iter()
+            .any(|(_, stick)| stick.kind == StickKind::Rope && stick.style == style));
+    }
+
+    #[test]
+    fn pinned_points_stay_together_but_turn() {
+        let mut ropes = Ropes::new();
+        let keys = [(0., 0.), (50., 0.), (50., 0.), (100., 0.)]
+            .map(|(x, y)| ropes.add_point(DVec2::new(x, y)));
+        ropes.toggle_locked(keys[0]);
+        ropes.add_stick(keys[0], keys[1], StickKind::Rod);
+        ropes.add_stick(keys[2], keys[3], StickKind::Rod);
+        ropes.add_pin(keys[1], keys[2]);
+        for _ in 0..100 {
+            ropes.tick();
+        }
+        let (pinned1, pinned2) = (
+            ropes.points[keys[1]].position,
+            ropes.points[keys[2]].position,
+        );
+        assert!(pinned1.distance(pinned2) < 1.);
+        let (arm1, arm2) = (pinned1, ropes.points[keys[3]].position - pinned2);
+        assert!(arm1.angle_between(arm2).abs() > 0.1);
+    }
+
+    #[test]
+    fn hanging_chains_are_most_tense_at_the_top() {
+        let mut ropes = Ropes::new();
+        ropes.seed(0);
+        let keys = [0., 20., 40., 60.].map(|y| ropes.add_point(DVec2::new(0., y)));
+        ropes.toggle_locked(keys[0]);
+        let sticks = [0, 1, 2].map(|i| {
+            ropes
+                .add_stick(keys[i], keys[i + 1], StickKind::Rod)
+                .unwrap()
+        });
+        for _ in 0..50 {
+            ropes.tick();
+        }
+        let tension = |i: usize| ropes.sticks[sticks[i]].tension;
+        assert!(tension(0) > tension(2) && tension(1) > tension(2) && tension(2) > 0.);
+    }
+
+    #[test]
+    fn falling_points_trade_potential_for_kinetic_energy() {
+        let mut ropes = Ropes::new();
+        let keys = [0., 20.].map(|y| ropes.add_point(DVec2::new(0., y)));
+        ropes.add_stick(keys[0], keys[1], StickKind::Rod);
+        let before = ropes.statistics();
+        for _ in 0..32 {
+            ropes.tick();
+        }
+        let after = ropes.statistics();
+        assert!(after.potential_energy < before.potential_energy);
+        let total = |s: &Statistics| s.kinetic_energy + s.potential_energy;
+        assert!((total(&after) - total(&before)).abs() < 0.05 * after.kinetic_energy);
+        assert!(after.rms_error < 1e-9);
+    }
+
+    #[test]
+    fn filling_finds_triangles_and_quads() {
+        let mut ropes = Ropes::new();
+        let keys: Vec<usize> = (0..9)
+            .map(|i| ropes.add_point(DVec2::new((i % 3) as f64, (i / 3) as f64) * 50.))
+            .collect();
+        for i in 0..9 {
+            if i % 3 < 2 {
+                ropes.add_stick(keys[i], keys[i + 1], StickKind::Rod);
+            }
+            if i < 6 {
+                ropes.add_stick(keys[i], keys[i + 3], StickKind::Rod);
+            }
+        }
+        ropes.add_stick(keys[0], keys[4], StickKind::Rod);
+
+        ropes.toggle_faces(keys[8]);
+        let mut sizes: Vec<usize> = ropes.faces.iter().map(|(_, f)| f.points.len()).collect();
+        sizes.sort();
+        assert_eq!(sizes, vec![3, 3, 4, 4, 4]);
+        assert!(ropes.get_faces(1.).flatten().all(|(position, uv)| {
+            (0. ..=1.).contains(&uv.x) && (0. ..=1.).contains(&uv.y) && position == uv * 100.
+        }));
+
+        ropes.toggle_faces(keys[0]);
+        assert!(ropes.faces.is_empty());
+    }
+
+    #[test]
+    fn chains_stop_at_branches_and_close_loops() {
+        let mut ropes = Ropes::new();
+        let ring = [(0., 0.), (50., 0.), (50., 50.), (0., 50.)]
+            .map(|(x, y)| ropes.add_point(DVec2::new(x, y)));
+        for i in 0..4 {
+            ropes.add_stick(ring[i], ring[(i + 1) % 4], StickKind::Rope);
+        }
+        let fork = [(200., 0.), (250., 0.), (300., 0.), (250., 50.), (350., 0.)]
+            .map(|(x, y)| ropes.add_point(DVec2::new(x, y)));
+        for (i, j) in [(0, 1), (1, 2), (1, 3), (2, 4)] {
+            ropes.add_stick(fork[i], fork[j], StickKind::Rod);
+        }
+
+        let mut chains: Vec<(usize, bool)> = ropes
+            .get_chains(1.)
+            .into_iter()
+            .map(|(positions, closed, ..)| (positions.len(), closed))
+            .collect();
+        chains.sort();
+        assert_eq!(chains, vec![(2, false), (2, false), (3, false), (4, true)]);
+    }
+
+    #[test]
+    fn loading_rebuilds_what_saving_skips() {
+        let mut ropes = Ropes::new();
+        let keys = [(0., 0.), (50., 0.), (100., 0.), (100., 50.)]
+            .map(|(x, y)| ropes.add_point(DVec2::new(x, y)));
+        for pair in keys.windows(2) {
+            ropes.add_stick(pair[0], pair[1], StickKind::Rod);
+        }
+        ropes.set_bending(keys[1], Some(0.1));
+        ropes.toggle_body(keys[3]);
+
+        let path = std::env::temp_dir().join(format!("ropes_round_trip_{}.json", process::id()));
+        ropes.save(&path).unwrap();
+        let loaded = Ropes::load(&path);
+        fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+
+        for (key, point) in ropes.points.iter() {
+            let other = &loaded.points[key];
+            assert_eq!(point.position, other.position);
+            assert_eq!(point.sticks, other.sticks);
+            assert_eq!(point.angles, other.angles);
+            assert_eq!(point.body, other.body);
+        }
+        assert_eq!(loaded.sticks.len(), ropes.sticks.len());
+        assert_eq!(loaded.get_point(DVec2::new(99., 1.), 12.), Some(keys[2]));
+    }
+
+    // saves the ropes to a temporary file and loads them back
+    fn reload(ropes: &Ropes, name: &str) -> io::Result<Ropes> {
+        let path = std::env::temp_dir().join(format!("ropes_{}_{}.json", name, process::id()));
+        ropes.save(&path).unwrap();
+        let loaded = Ropes::load(&path);
+        fs::remove_file(&path).unwrap();
+        loaded
+    }
+
+    #[test]
+    fn loading_rejects_missing_points() {
+        let mut ropes = Ropes::new();
+        let keys = [(0., 0.), (50., 0.)].map(|(x, y)| ropes.add_point(DVec2::new(x, y)));
+        ropes.add_stick(keys[0], keys[1], StickKind::Rope);
+        ropes.points.remove(keys[1]);
+
+        let loaded = reload(&ropes, "missing");
+        assert_eq!(loaded.err().unwrap().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn loading_rejects_malformed_sticks_and_angles() {
+        let mut ropes = Ropes::new();
+        let keys = [(0., 0.), (50., 0.)].map(|(x, y)| ropes.add_point(DVec2::new(x, y)));
+        let stick = ropes.add_stick(keys[0], keys[1], StickKind::Rod).unwrap();
+        ropes.sticks[stick].points = (keys[0], keys[0]);
+        let loaded = reload(&ropes, "loop");
+        assert_eq!(loaded.err().unwrap().kind(), io::ErrorKind::InvalidData);
+
+        // an angle whose second stick doesn't reach its pivot
+        let mut ropes = Ropes::new();
+        let keys = [(0., 0.), (50., 0.), (50., 50.), (100., 50.)]
+            .map(|(x, y)| ropes.add_point(DVec2::new(x, y)));
+        for pair in keys.windows(2) {
+            ropes.add_stick(pair[0], pair[1], StickKind::Rod);
+        }
+        ropes.set_bending(keys[1], Some(0.1));
+        assert!(reload(&ropes, "angle").is_ok());
+        let angle = ropes.points[keys[1]].angles[0];
+        let stick = ropes.points[keys[3]].sticks[0];
+        ropes.angles[angle].sticks.1 = stick;
+        let loaded = reload(&ropes, "angle");
+        assert_eq!(loaded.err().unwrap().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn loading_rejects_malformed_water() {
+        let square = vec![DVec2::ZERO, DVec2::X, DVec2::ONE, DVec2::Y];
+        let waters = [
+            Water::new(Vec::new()),
+            Water::new(square[..2].to_vec()),
+            Water::new(vec![DVec2::ZERO, DVec2::X, DVec2::NAN]),
+            Water {
+                drag: f64::NAN,
+                ..Water::new(square.clone())
+            },
+            Water {
+                buoyancy: f64::INFINITY,
+                ..Water::new(square.clone())
+            },
+        ];
+        for water in waters {
+            let mut ropes = Ropes::new();
+            ropes.waters.insert(water);
+            let loaded = reload(&ropes, "water");
+            assert_eq!(loaded.err().unwrap().kind(), io::ErrorKind::InvalidData);
+        }
+
+        let mut ropes = Ropes::new();
+        ropes.add_water(square);
+        assert!(reload(&ropes, "water").is_ok());
+    }
+
+    #[test]
+    fn picking_returns_the_nearest_point() {
+        let mut ropes = Ropes::new();
+        let far = ropes.add_point(DVec2::new(8., 0.));
+        let near = ropes.add_point(DVec2::new(3., 0.));
+        ropes.add_point(DVec2::new(30., 0.));
+        assert_eq!(ropes.get_point(DVec2::ZERO, 12.), Some(near));
+        assert_eq!(ropes.get_point(DVec2::new(10., 0.), 12.), Some(far));
+    }
+
+    #[test]
+    fn oversized_points_are_still_deleted() {
+        let mut ropes = Ropes::new();
+        let key = ropes.add_point(DVec2::new(5000., 0.));
+        ropes.points[key].last_position = DVec2::new(-5000., 0.);
+        ropes.index();
+        assert_eq!(ropes.point_grid.query(DVec2::ZERO, DVec2::ONE), vec![key]);
+        ropes.remove_points(DVec2::new(0., -100.), DVec2::new(0., 100.), 12.);
+        assert!(ropes.points.is_empty());
+    }
+
+    // a square of points joined to their neighbours, hanging from its top row
+    fn cloth(size: usize) -> Ropes {
+        let mut ropes = Ropes::new();
+        let keys: Vec<usize> = (0..size * size)
+            .map(|i| ropes.add_point(DVec2::new((i % size) as f64, (i / size) as f64) * 20.))
+            .collect();
+        for &key in keys.iter().take(size) {
+            ropes.toggle_locked(key);
+        }
+        for (i, &key) in keys.iter().enumerate() {
+            if i % size + 1 < size {
+                ropes.add_stick(key, keys[i + 1], StickKind::Rod);
+            }
+            if i + size < keys.len() {
+                ropes.add_stick(key, keys[i + size], StickKind::Rod);
+            }
+        }
+        ropes.seed(0);
+        ropes
+    }
+
+    #[test]
+    fn parallel_ticks_keep_cloth_lengths_like_sequential_ones() {
+        let mut sequential = cloth(16);
+        let mut parallel = cloth(16);
+        parallel.set_parallel(true);
+        for _ in 0..120 {
+            sequential.tick();
+            parallel.tick();
+        }
+        let (sequential, parallel) = (sequential.statistics(), parallel.statistics());
+        // the cloth stretches a little under its own weight either way, but not by much more
+        assert!(parallel.rms_error < 2.);
+        assert!(parallel.rms_error < sequential.rms_error * 1.5);
+    }
+
+    #[test]
+    fn sliders_stay_on_their_rails() {
+        let mut ropes = Ropes::new();
+        let rail1 = ropes.add_point(DVec2::ZERO);
+        let rail2 = ropes.add_point(DVec2::new(100., 100.));
+        let point = ropes.add_point(DVec2::new(0., 50.));
+        ropes.toggle_locked(rail1);
+        ropes.toggle_locked(rail2);
+        ropes.add_slider(point, rail1, rail2);
+        ropes.add_slider(point, point, rail2);
+        assert_eq!(ropes.sliders.len(), 1);
+
+        for _ in 0..100 {
+            ropes.tick();
+            let position = ropes.points[point].position;
+            assert!((position.x - position.y).abs() < 1e-9);
+        }
+        assert!(
+            ropes.points[point]
+                .position
+                .distance(DVec2::new(100., 100.))
+                < 1e-9
+        );
+    }
+
+    #[test]
+    fn mouse_moving_along_stick_onto_it_crosses_it() {
+        let (a, b) = (DVec2::new(0., 100.), DVec2::new(100., 100.));
+        let (m0, m1) = (DVec2::new(-50., 100.), DVec2::new(40., 100.));
+        assert!(intersects_stick(m0, m1, a, a, b, b));
+        assert!(!intersects_stick(m0, m0, a, a, b, b));
+    }
+
+    #[test]
+    fn approx_eq_is_relative_to_scale() {
+        assert!(approx_eq(1e6 + 1e-7, 1e6, 1e6));
+        assert!(!approx_eq(1e-10, 0., 1.));
+    }
+
+    proptest! {
+        #[test]
+        fn point_matches_sampling(
+            m0 in vector(), m1 in vector(), c0 in vector(), c1 in vector(), radius in 1.0..100.0
+        ) {
+            if let Some(expected) = sampled_point(m0, m1, c0, c1, radius) {
+                prop_assert_eq!(intersects_point(m0, m1, c0, c1, radius), expected);
+            }
+        }
+
+        #[test]
+        fn point_matches_sampling_on_grid(
+            m0 in grid_vector(), m1 in grid_vector(), c0 in grid_vector(), c1 in grid_vector()
+        ) {
+            if let Some(expected) = sampled_point(m0, m1, c0, c1, 12.) {
+                prop_assert_eq!(intersects_point(m0, m1, c0, c1, 12.), expected);
+            }
+        }
+
+        #[test]
+        fn stick_matches_sampling(
+            m0 in vector(), m1 in vector(), a0 in vector(), a1 in vector(), b0 in vector(), b1 in vector()
+        ) {
+            if let Some(expected) = sampled_stick(m0, m1, a0, a1, b0, b1) {
+                prop_assert_eq!(intersects_stick(m0, m1, a0, a1, b0, b1), expected);
+            }
+        }
+
+        #[test]
+        fn stick_matches_sampling_on_grid(
+            m0 in grid_vector(), m1 in grid_vector(), a0 in grid_vector(), a1 in grid_vector(), b0 in grid_vector(), b1 in grid_vector()
+        ) {
+            if let Some(expected) = sampled_stick(m0, m1, a0, a1, b0, b1) {
+                prop_assert_eq!(intersects_stick(m0, m1, a0, a1, b0, b1), expected);
+            }
+        }
+
+        #[test]
+        fn stick_matches_sampling_with_parallel_motion(
+            m0 in vector(), m1 in vector(), a0 in vector(), b0 in vector(), scale in 0.0..2.0
+        ) {
+            let motion = (m1 - m0) * scale;
+            let (a1, b1) = (a0 + motion, b0 + motion);
+            if let Some(expected) = sampled_stick(m0, m1, a0, a1, b0, b1) {
+                prop_assert_eq!(intersects_stick(m0, m1, a0, a1, b0, b1), expected);
+            }
+        }
+
+        #[test]
+        fn stick_matches_sampling_when_stationary(
+            m0 in vector(), m1 in vector(), a in vector(), b in vector()
+        ) {
+            if let Some(expected) = sampled_stick(m0, m1, a, a, b, b) {
+                prop_assert_eq!(intersects_stick(m0, m1, a, a, b, b), expected);
+            }
+        }
+
+        #[test]
+        fn no_colour_shares_a_free_point(
+            (points, sticks) in scene_strategy(), locked in prop::collection::vec(any::<bool>(), 24)
+        ) {
+            let mut ropes = scene(&points, &sticks);
+            let keys: Vec<usize> = ropes.points.iter().map(|(key, _)| key).collect();
+            for (key, _) in keys.into_iter().zip(locked).filter(|&(_, locked)| locked) {
+                ropes.toggle_locked(key);
+            }
+            let colours = colouring::colour(&ropes.points, &ropes.sticks);
+            let mut coloured: Vec<usize> = colours.iter().flatten().copied().collect();
+            coloured.sort_unstable();
+            let all: Vec<usize> = ropes.sticks.iter().map(|(key, _)| key).collect();
+            prop_assert_eq!(coloured, all);
+            for colour in colours.iter() {
+                let mut seen = HashSet::new();
+                for &key in colour.iter() {
+                    let (point1, point2) = ropes.sticks[key].points;
+                    for point in [point1, point2] {
+                        prop_assert!(ropes.points[point].locked || seen.insert(point));
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn picking_matches_linear_scan(
+            (points, sticks) in scene_strategy(), position in vector(), distance in 1.0..200.0
+        ) {
+            let ropes = scene(&points, &sticks);
+            let expected = ropes
+                .points
+                .iter()
+                .map(|(key, point)| (key, (point.position - position).length()))
+                .filter(|&(_, d)| d < distance)
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(key, _)| key);
+            prop_assert_eq!(ropes.get_point(position, distance), expected);
+        }
+
+        #[test]
+        fn deleting_points_matches_linear_scan(
+            (points, sticks) in scene_strategy(), last in motion(), current in motion()
+        ) {
+            let mut ropes = scene(&points, &sticks);
+            let kept: Vec<usize> = ropes
+                .points
+                .iter()
+                .filter(|(_, point)| {
+                    !intersects_point(last, current, point.last_position, point.position, 12.)
+                })
+                .map(|(key, _)| key)
+                .collect();
+            ropes.remove_points(last, current, 12.);
+            let remaining: Vec<usize> = ropes.points.iter().map(|(key, _)| key).collect();
+            prop_assert_eq!(remaining, kept);
+        }
+
+        #[test]
+        fn deleting_sticks_matches_linear_scan(
+            (points, sticks) in scene_strategy(), last in motion(), current in motion()
+        ) {
+            let mut ropes = scene(&points, &sticks);
+            let kept: Vec<usize> = ropes
+                .sticks
+                .iter()
+                .filter(|(_, stick)| {
+                    let (point1, point2) = (&ropes.points[stick.points.0], &ropes.points[stick.points.1]);
+                    !intersects_stick(
+                        last,
+                        current,
+                        point1.last_position,
+                        point1.position,
+                        point2.last_position,
+                        point2.position,
+                    )
+                })
+                .map(|(key, _)| key)
+                .collect();
+            ropes.remove_sticks(last, current);
+            let remaining: Vec<usize> = ropes.sticks.iter().map(|(key, _)| key).collect();
+            prop_assert_eq!(remaining, kept);
+        }
+
+        #[test]
+        fn zero_length_stick_is_never_crossed(
+            m0 in vector(), m1 in vector(), a0 in vector(), a1 in vector()
+        ) {
+            prop_assert!(!intersects_stick(m0, m1, a0, a1, a0, a1));
+        }
+    }
 }
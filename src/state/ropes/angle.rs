@@ -0,0 +1,64 @@
+use super::Point;
+use glam::DVec2;
+use serde::{Deserialize, Serialize};
+use slab::Slab;
+use std::f64::consts::{PI, TAU};
+
+/// Bends two sticks meeting at a pivot back towards the angle they were joined at.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Angle {
+    // the pivot is in the middle
+    pub(super) points: (usize, usize, usize),
+    pub(super) sticks: (usize, usize),
+    pub(super) angle: f64,
+    pub(super) stiffness: f64,
+}
+
+impl Angle {
+    pub(super) fn new(
+        points: (usize, usize, usize),
+        sticks: (usize, usize),
+        stiffness: f64,
+        slab: &Slab<Point>,
+    ) -> Self {
+        let (end1, pivot, end2) = (&slab[points.0], &slab[points.1], &slab[points.2]);
+        Self {
+            points,
+            sticks,
+            angle: between(
+                end1.position - pivot.position,
+                end2.position - pivot.position,
+            ),
+            stiffness,
+        }
+    }
+
+    pub(super) fn solve(&self, points: &Slab<Point>) -> (DVec2, DVec2) {
+        let (end1, pivot, end2) = (
+            &points[self.points.0],
+            &points[self.points.1],
+            &points[self.points.2],
+        );
+        let (arm1, arm2) = (
+            end1.position - pivot.position,
+            end2.position - pivot.position,
+        );
+        let error = (between(arm1, arm2) - self.angle + PI).rem_euclid(TAU) - PI;
+        let correction = error * self.stiffness;
+        let (share1, share2) = match (end1.locked, end2.locked) {
+            (false, false) => (0.5, 0.5),
+            (true, false) => (0., 1.),
+            (false, true) => (1., 0.),
+            (true, true) => (0., 0.),
+        };
+        (
+            pivot.position + DVec2::from_angle(correction * share1).rotate(arm1),
+            pivot.position + DVec2::from_angle(-correction * share2).rotate(arm2),
+        )
+    }
+}
+
+// the signed angle to turn from the first direction to the second
+fn between(from: DVec2, to: DVec2) -> f64 {
+    from.perp_dot(to).atan2(from.dot(to))
+}
@@ -0,0 +1,71 @@
+use super::Point;
+use glam::DVec2;
+use serde::{Deserialize, Serialize};
+use slab::Slab;
+
+// how much more a fixed point counts than a free one when matching the shape
+const LOCKED_WEIGHT: f64 = 1e6;
+
+/// Holds a group of points in the shape they were grouped in, letting the whole group move and
+/// turn but not bend.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Body {
+    pub(super) points: Vec<usize>,
+    rest: Vec<DVec2>,
+}
+
+impl Body {
+    pub(super) fn new(points: Vec<usize>, slab: &Slab<Point>) -> Self {
+        let rest = points.iter().map(|&key| slab[key].position).collect();
+        Self { points, rest }
+    }
+
+    pub(super) fn valid(&self) -> bool {
+        self.points.len() == self.rest.len()
+    }
+
+    // the positions that best fit the rest shape onto where the points currently are
+    pub(super) fn solve(&self, points: &Slab<Point>) -> Vec<DVec2> {
+        let weights: Vec<f64> = self
+            .points
+            .iter()
+            .map(|&key| {
+                if points[key].locked {
+                    LOCKED_WEIGHT
+                } else {
+                    1.
+                }
+            })
+            .collect();
+        let total: f64 = weights.iter().sum();
+        let centre = |positions: &mut dyn Iterator<Item = DVec2>| {
+            positions
+                .zip(weights.iter())
+                .fold(DVec2::ZERO, |sum, (p, w)| sum + p * *w)
+                / total
+        };
+        let rest_centre = centre(&mut self.rest.iter().copied());
+        let current_centre = centre(&mut self.points.iter().map(|&key| points[key].position));
+
+        let (mut cos, mut sin) = (0., 0.);
+        for ((&key, &rest), &weight) in self.points.iter().zip(self.rest.iter()).zip(weights.iter())
+        {
+            let (from, to) = (rest - rest_centre, points[key].position - current_centre);
+            cos += weight * from.dot(to);
+            sin += weight * from.perp_dot(to);
+        }
+        let rotation = DVec2::new(cos, sin).try_normalize().unwrap_or(DVec2::X);
+
+        self.rest
+            .iter()
+            .map(|&rest| current_centre + rotation.rotate(rest - rest_centre))
+            .collect()
+    }
+
+    pub(super) fn remove(&mut self, key: usize) {
+        if let Some(i) = self.points.iter().position(|&point| point == key) {
+            self.points.swap_remove(i);
+            self.rest.swap_remove(i);
+        }
+    }
+}
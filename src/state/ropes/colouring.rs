@@ -0,0 +1,29 @@
+use super::{Point, Stick};
+use slab::Slab;
+
+/// Greedily colours the sticks so that no two sticks of the same colour share a free point,
+/// meaning every stick of one colour can be solved at the same time.
+pub fn colour(points: &Slab<Point>, sticks: &Slab<Stick>) -> Vec<Vec<usize>> {
+    let mut colours: Vec<Vec<usize>> = Vec::new();
+    let mut stick_colours = vec![usize::MAX; sticks.capacity()];
+    let mut used = Vec::new();
+
+    for (key, stick) in sticks.iter() {
+        used.clear();
+        for point in [stick.points.0, stick.points.1] {
+            let point = &points[point];
+            if !point.locked {
+                used.extend(point.sticks.iter().map(|&stick| stick_colours[stick]));
+            }
+        }
+
+        let colour = (0..).find(|colour| !used.contains(colour)).unwrap();
+        if colour == colours.len() {
+            colours.push(Vec::new());
+        }
+        colours[colour].push(key);
+        stick_colours[key] = colour;
+    }
+
+    colours
+}
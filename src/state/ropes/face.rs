@@ -0,0 +1,52 @@
+use glam::DVec2;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// A triangle or quad of points drawn as a surface, with texture coordinates from where its
+/// points were when it was filled.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Face {
+    pub(super) points: Vec<usize>,
+    pub(super) uvs: Vec<DVec2>,
+}
+
+impl Face {
+    pub(super) fn new(points: Vec<usize>, uvs: Vec<DVec2>) -> Self {
+        Self { points, uvs }
+    }
+
+    pub(super) fn valid(&self) -> bool {
+        (3..=4).contains(&self.points.len()) && self.points.len() == self.uvs.len()
+    }
+
+    pub(super) fn contains(&self, key: usize) -> bool {
+        self.points.contains(&key)
+    }
+}
+
+// every triangle of sticks among the points, and every quad without a diagonal across it, with
+// their points in order around them
+pub(super) fn find(adjacent: &HashMap<usize, HashSet<usize>>) -> Vec<Vec<usize>> {
+    let mut faces = Vec::new();
+    for (&a, around) in adjacent.iter() {
+        for &b in around.iter().filter(|&&b| b > a) {
+            for &c in around.iter().filter(|&&c| c > b) {
+                if adjacent[&b].contains(&c) {
+                    faces.push(vec![a, b, c]);
+                }
+            }
+            // each quad is found once, from its lowest point going to the lower of its two sides
+            for &d in around
+                .iter()
+                .filter(|&&d| d > b && !adjacent[&b].contains(&d))
+            {
+                for &c in adjacent[&b].iter() {
+                    if c > a && adjacent[&d].contains(&c) && !around.contains(&c) {
+                        faces.push(vec![a, b, c, d]);
+                    }
+                }
+            }
+        }
+    }
+    faces
+}
@@ -0,0 +1,86 @@
+use glam::DVec2;
+use serde::{Deserialize, Serialize};
+
+/// Something pushing on the points besides gravity and the sticks, all in pixels and seconds.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Field {
+    /// Moves the air everywhere, gusting and swirling by up to `turbulence` times its speed. It
+    /// only pushes on sticks, through their drag.
+    Wind { velocity: DVec2, turbulence: f64 },
+    /// Pulls points within `radius` towards its centre, or pushes them away if `strength` is
+    /// negative, most strongly at the centre.
+    Radial {
+        centre: DVec2,
+        radius: f64,
+        strength: f64,
+    },
+    /// Spins points within `radius` around its centre, clockwise on screen if `strength` is
+    /// positive.
+    Vortex {
+        centre: DVec2,
+        radius: f64,
+        strength: f64,
+    },
+}
+
+impl Field {
+    pub fn centre(&self) -> Option<DVec2> {
+        match *self {
+            Field::Wind { .. } => None,
+            Field::Radial { centre, .. } | Field::Vortex { centre, .. } => Some(centre),
+        }
+    }
+
+    pub(super) fn acceleration(&self, position: DVec2) -> DVec2 {
+        match *self {
+            Field::Wind { .. } => DVec2::ZERO,
+            Field::Radial {
+                centre,
+                radius,
+                strength,
+            } => {
+                let offset = centre - position;
+                offset.normalize_or_zero() * strength * falloff(offset.length(), radius)
+            }
+            Field::Vortex {
+                centre,
+                radius,
+                strength,
+            } => {
+                let offset = position - centre;
+                offset.perp().normalize_or_zero() * strength * falloff(offset.length(), radius)
+            }
+        }
+    }
+
+    pub(super) fn air(&self, position: DVec2, time: f64) -> DVec2 {
+        match *self {
+            Field::Wind {
+                velocity,
+                turbulence,
+            } => {
+                let gust = noise(position, time, 0.) * turbulence;
+                let swirl = noise(position, time, 10.) * turbulence;
+                velocity * (1. + gust) + velocity.perp() * swirl
+            }
+            _ => DVec2::ZERO,
+        }
+    }
+}
+
+fn falloff(distance: f64, radius: f64) -> f64 {
+    (1. - distance / radius).max(0.)
+}
+
+// smoothly varying values between -1 and 1, drifting through space and time
+fn noise(position: DVec2, time: f64, seed: f64) -> f64 {
+    let waves = [
+        (DVec2::new(0.011, 0.004), 1.3, 0.5),
+        (DVec2::new(-0.006, 0.013), 2.1, 0.3),
+        (DVec2::new(0.023, -0.017), 3.7, 0.2),
+    ];
+    waves
+        .iter()
+        .map(|&(k, w, amplitude)| (k.dot(position) - w * time + seed * w).sin() * amplitude)
+        .sum()
+}
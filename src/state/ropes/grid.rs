@@ -0,0 +1,97 @@
+use glam::{DVec2, IVec2};
+use std::collections::HashMap;
+
+const CELL_SIZE: f64 = 64.;
+const MAX_CELLS: f64 = 256.;
+
+/// A spatial hash of keys by bounding box, used as the broad phase for picking and deleting.
+#[derive(Clone)]
+pub struct Grid<K> {
+    cells: HashMap<IVec2, Vec<K>>,
+    // keys with boxes covering too many cells, such as exploded sticks, are always returned
+    oversized: Vec<K>,
+}
+
+impl<K: Copy + Ord> Grid<K> {
+    pub fn new() -> Self {
+        Self {
+            cells: HashMap::new(),
+            oversized: Vec::new(),
+        }
+    }
+
+    pub fn insert(&mut self, key: K, min: DVec2, max: DVec2) {
+        match cells(min, max) {
+            Some((min, max)) => {
+                for x in min.x..=max.x {
+                    for y in min.y..=max.y {
+                        self.cells.entry(IVec2::new(x, y)).or_default().push(key);
+                    }
+                }
+            }
+            None => self.oversized.push(key),
+        }
+    }
+
+    pub fn remove(&mut self, key: K, min: DVec2, max: DVec2) {
+        match cells(min, max) {
+            Some((min, max)) => {
+                for x in min.x..=max.x {
+                    for y in min.y..=max.y {
+                        if let Some(cell) = self.cells.get_mut(&IVec2::new(x, y)) {
+                            cell.retain(|&k| k != key);
+                        }
+                    }
+                }
+            }
+            None => self.oversized.retain(|&k| k != key),
+        }
+    }
+
+    /// Returns every key whose box may overlap the given box, without duplicates.
+    pub fn query(&self, min: DVec2, max: DVec2) -> Vec<K> {
+        let mut keys = self.oversized.clone();
+        match cells(min, max) {
+            Some((min, max)) => {
+                for x in min.x..=max.x {
+                    for y in min.y..=max.y {
+                        if let Some(cell) = self.cells.get(&IVec2::new(x, y)) {
+                            keys.extend_from_slice(cell);
+                        }
+                    }
+                }
+            }
+            None => keys.extend(self.cells.values().flatten()),
+        }
+        keys.sort_unstable();
+        keys.dedup();
+        keys
+    }
+
+    /// Empties the grid, keeping the allocations of cells that were in use.
+    pub fn clear(&mut self) {
+        self.cells.retain(|_, cell| {
+            let used = !cell.is_empty();
+            cell.clear();
+            used
+        });
+        self.oversized.clear();
+    }
+}
+
+impl<K: Copy + Ord> Default for Grid<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn cells(min: DVec2, max: DVec2) -> Option<(IVec2, IVec2)> {
+    let min = (min / CELL_SIZE).floor();
+    let max = (max / CELL_SIZE).floor();
+    let size = (max - min).max(DVec2::ZERO) + 1.;
+    if size.x * size.y <= MAX_CELLS {
+        Some((min.as_ivec2(), max.as_ivec2()))
+    } else {
+        None
+    }
+}
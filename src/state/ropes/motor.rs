@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+use std::f64::consts::TAU;
+
+// the shortest a winch can reel a stick in to
+const MIN_LENGTH: f64 = 1.;
+
+/// Drives a stick's length, in pixels and seconds.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Motor {
+    /// Reels the stick in or out at `speed` while the winches are being wound.
+    Winch { speed: f64 },
+    /// Stretches and shrinks the stick by `amplitude` around `length` once every `period`.
+    Oscillate {
+        length: f64,
+        amplitude: f64,
+        period: f64,
+    },
+}
+
+impl Motor {
+    // the stick's length after a tick of `duration`, with the winches wound by `winding` from -1
+    // (in) to 1 (out)
+    pub(super) fn drive(&self, length: f64, time: f64, duration: f64, winding: f64) -> f64 {
+        match *self {
+            Motor::Winch { speed } => (length + speed * winding * duration).max(MIN_LENGTH),
+            Motor::Oscillate {
+                length,
+                amplitude,
+                period,
+            } => length + amplitude * (TAU * time / period).sin(),
+        }
+    }
+}
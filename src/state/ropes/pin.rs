@@ -0,0 +1,29 @@
+use super::Point;
+use glam::DVec2;
+use serde::{Deserialize, Serialize};
+use slab::Slab;
+
+/// Holds two points together while leaving whatever they are part of free to turn about them.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Pin {
+    pub(super) points: (usize, usize),
+}
+
+impl Pin {
+    pub(super) fn new(points: (usize, usize)) -> Self {
+        Self { points }
+    }
+
+    pub(super) fn solve(&self, points: &Slab<Point>) -> DVec2 {
+        let (point1, point2) = (&points[self.points.0], &points[self.points.1]);
+        match (point1.locked, point2.locked) {
+            (true, false) => point1.position,
+            (false, true) => point2.position,
+            _ => (point1.position + point2.position) / 2.,
+        }
+    }
+
+    pub(super) fn contains(&self, key: usize) -> bool {
+        key == self.points.0 || key == self.points.1
+    }
+}
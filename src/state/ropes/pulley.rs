@@ -0,0 +1,55 @@
+use super::Point;
+use glam::DVec2;
+use serde::{Deserialize, Serialize};
+use slab::Slab;
+
+/// A rope running from one point over a wheel to another, so that either side can only get
+/// longer by pulling in the other side.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Pulley {
+    // the wheel is in the middle, and is never moved by the pulley
+    pub(super) points: (usize, usize, usize),
+    pub(super) length: f64,
+}
+
+impl Pulley {
+    pub(super) fn new(points: (usize, usize, usize), slab: &Slab<Point>) -> Self {
+        let (end1, wheel, end2) = (&slab[points.0], &slab[points.1], &slab[points.2]);
+        Self {
+            points,
+            length: (end1.position - wheel.position).length()
+                + (end2.position - wheel.position).length(),
+        }
+    }
+
+    pub(super) fn solve(&self, points: &Slab<Point>) -> (DVec2, DVec2) {
+        let (end1, wheel, end2) = (
+            &points[self.points.0],
+            &points[self.points.1],
+            &points[self.points.2],
+        );
+        let (side1, side2) = (
+            end1.position - wheel.position,
+            end2.position - wheel.position,
+        );
+        let excess = side1.length() + side2.length() - self.length;
+        let (share1, share2) = match (end1.locked, end2.locked) {
+            (false, false) => (0.5, 0.5),
+            (true, false) => (0., 1.),
+            (false, true) => (1., 0.),
+            (true, true) => (0., 0.),
+        };
+        if excess <= 0. {
+            return (end1.position, end2.position);
+        }
+        (
+            end1.position - side1.normalize_or_zero() * excess * share1,
+            end2.position - side2.normalize_or_zero() * excess * share2,
+        )
+    }
+
+    pub(super) fn contains(&self, key: usize) -> bool {
+        let (end1, wheel, end2) = self.points;
+        key == end1 || key == wheel || key == end2
+    }
+}
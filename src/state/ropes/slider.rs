@@ -0,0 +1,34 @@
+use super::Point;
+use glam::DVec2;
+use serde::{Deserialize, Serialize};
+use slab::Slab;
+
+/// Keeps a point on the rail between two others, free to slide along it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Slider {
+    pub(super) point: usize,
+    // the rail's ends are never moved by the slider
+    pub(super) rail: (usize, usize),
+}
+
+impl Slider {
+    pub(super) fn new(point: usize, rail: (usize, usize)) -> Self {
+        Self { point, rail }
+    }
+
+    pub(super) fn solve(&self, points: &Slab<Point>) -> DVec2 {
+        let position = points[self.point].position;
+        let (a, b) = (points[self.rail.0].position, points[self.rail.1].position);
+        let rail = b - a;
+        let length_squared = rail.length_squared();
+        if length_squared == 0. {
+            return a;
+        }
+        let t = ((position - a).dot(rail) / length_squared).clamp(0., 1.);
+        a + rail * t
+    }
+
+    pub(super) fn contains(&self, key: usize) -> bool {
+        key == self.point || key == self.rail.0 || key == self.rail.1
+    }
+}
@@ -0,0 +1,59 @@
+use glam::DVec2;
+use serde::{Deserialize, Serialize};
+
+const BUOYANCY: f64 = 1500.;
+const DRAG: f64 = 2.;
+
+/// A polygon of water that floats the points inside it, slowing them down as they move.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Water {
+    pub(super) polygon: Vec<DVec2>,
+    // upwards, in pixels per second squared, so above gravity floats points
+    #[serde(default = "default_buoyancy")]
+    pub(super) buoyancy: f64,
+    // the fraction of a point's speed taken away per second
+    #[serde(default = "default_drag")]
+    pub(super) drag: f64,
+}
+
+impl Water {
+    pub(super) fn new(polygon: Vec<DVec2>) -> Self {
+        Self {
+            polygon,
+            buoyancy: BUOYANCY,
+            drag: DRAG,
+        }
+    }
+
+    pub(super) fn valid(&self) -> bool {
+        self.polygon.len() >= 3
+            && self.polygon.iter().all(|vertex| vertex.is_finite())
+            && self.buoyancy.is_finite()
+            && self.drag.is_finite()
+    }
+
+    pub(super) fn contains(&self, position: DVec2) -> bool {
+        // counts the edges crossed by a ray to the right of the position
+        let mut inside = false;
+        let mut last = self.polygon[self.polygon.len() - 1];
+        for &vertex in self.polygon.iter() {
+            if (vertex.y > position.y) != (last.y > position.y) {
+                let x =
+                    vertex.x + (position.y - vertex.y) / (last.y - vertex.y) * (last.x - vertex.x);
+                if position.x < x {
+                    inside = !inside;
+                }
+            }
+            last = vertex;
+        }
+        inside
+    }
+}
+
+fn default_buoyancy() -> f64 {
+    BUOYANCY
+}
+
+fn default_drag() -> f64 {
+    DRAG
+}
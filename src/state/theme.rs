@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::Path};
+
+pub const NAMES: [&str; 3] = ["dark", "light", "high-contrast"];
+
+// missing fields in a theme file fall back to the dark theme
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub background: (u8, u8, u8),
+    pub point: (u8, u8, u8),
+    pub locked: (u8, u8, u8),
+    pub stick: (u8, u8, u8),
+    pub rope: (u8, u8, u8),
+    // filled faces without a texture
+    pub cloth: (u8, u8, u8),
+    pub text: (u8, u8, u8),
+    // the background grid and the lines through the origin
+    pub grid: (u8, u8, u8),
+    pub axes: (u8, u8, u8),
+    pub stick_width: f32,
+    pub rope_width: f32,
+    // how big points are drawn relative to the area they can be picked from
+    pub point_scale: f32,
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            background: (61, 64, 112),
+            point: (255, 255, 255),
+            locked: (255, 0, 0),
+            stick: (203, 203, 212),
+            rope: (190, 160, 110),
+            cloth: (110, 90, 160),
+            text: (255, 255, 255),
+            grid: (72, 75, 126),
+            axes: (105, 110, 170),
+            stick_width: 5.,
+            rope_width: 2.,
+            point_scale: 1.,
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            background: (236, 236, 242),
+            point: (40, 40, 56),
+            locked: (220, 30, 30),
+            stick: (96, 96, 120),
+            rope: (150, 105, 50),
+            cloth: (170, 190, 225),
+            text: (20, 20, 30),
+            grid: (218, 218, 228),
+            axes: (175, 175, 195),
+            ..Self::dark()
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Self {
+            background: (0, 0, 0),
+            point: (255, 255, 255),
+            locked: (255, 40, 40),
+            stick: (255, 255, 0),
+            rope: (0, 255, 255),
+            cloth: (0, 0, 160),
+            text: (255, 255, 255),
+            grid: (45, 45, 45),
+            axes: (140, 140, 140),
+            stick_width: 7.,
+            rope_width: 4.,
+            point_scale: 1.25,
+        }
+    }
+
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            _ => None,
+        }
+    }
+
+    // a built in theme by name, or otherwise a theme file
+    pub fn find(name: &str) -> io::Result<Self> {
+        match Self::named(name) {
+            Some(theme) => Ok(theme),
+            None => Self::load(name),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = fs::File::open(path)?;
+        Ok(serde_json::from_reader(io::BufReader::new(file))?)
+    }
+
+    // the built in theme after this one, starting again from the first after the last or a
+    // theme loaded from a file
+    pub fn next(&self) -> Self {
+        let current = NAMES
+            .iter()
+            .position(|name| Self::named(name).as_ref() == Some(self));
+        let next = current.map_or(0, |i| (i + 1) % NAMES.len());
+        Self::named(NAMES[next]).unwrap()
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}